@@ -0,0 +1,372 @@
+use std::any::Any;
+
+// https://gbdev.io/pandocs/Memory_Map.html
+
+/// Anything the CPU can read from or write to through the address bus
+pub trait Bus: Any {
+    fn read(&mut self, address: u16) -> u8;
+    fn write(&mut self, address: u16, value: u8);
+
+    /// What a read would return, without any of its side effects
+    fn peek(&self, address: u16) -> u8;
+    /// Store straight into the backing memory, even ROM, bypassing register writes
+    fn poke(&mut self, address: u16, value: u8);
+
+    /// Let cycles of the CPU clock go by
+    fn tick(&mut self, _cycles: u32) { }
+}
+
+/*
+ * [0000, 3FFF] ROM Bank 00 -> Cartridge
+ * [4000, 7FFF] ROM Bank 01~NN -> Cartridge
+ * [8000, 9FFF] VRAM
+ * [A000, BFFF] External RAM -> Cartridge
+ * [C000, CFFF] Work RAM
+ * [D000, DFFF] Work RAM
+ * [E000, FDFF] ECHO RAM - Mirror of C000~DDFF - Prohibited Access
+ * [FE00, FE9F] OAM
+ * [FEA0, FEFF] Not Usable - Prohibited, reads 0x00 or 0xFF on DMG
+ * [FF00, FF7F] I/O Registers
+ * [FF80, FFFE] High RAM
+ * [FFFF] Interrupt Enable Register
+ */
+
+// Bits that always read back as 1 on DMG, whole registers for unmapped ones
+// https://gbdev.io/pandocs/Hardware_Reg_List.html
+const IO_UNUSED_BITS: [u8; 0x80] = [
+    // 0xFF0_ P1, SB, SC, DIV, TIMA, TMA, TAC, IF
+    0xC0, 0x00, 0x7E, 0xFF, 0x00, 0x00, 0x00, 0xF8, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xE0,
+    // 0xFF1_ NR10~NR34
+    0x80, 0x3F, 0x00, 0xFF, 0xBF, 0xFF, 0x3F, 0x00, 0xFF, 0xBF, 0x7F, 0xFF, 0x9F, 0xFF, 0xBF, 0xFF,
+    // 0xFF2_ NR41~NR52
+    0xFF, 0x00, 0x00, 0xBF, 0x00, 0x00, 0x70, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF,
+    // 0xFF3_ Wave RAM
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+    // 0xFF4_ LCDC, STAT, SCY, SCX, LY, LYC, DMA, BGP, OBP0, OBP1, WY, WX
+    0x00, 0x80, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0xFF, 0xFF, 0xFF, 0xFF,
+    // 0xFF5_ ~ 0xFF7_ CGB only or unused
+    0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF,
+    0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF,
+    0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF,
+];
+
+const DIVIDER: usize = 0x04;
+const LCD_STATUS: usize = 0x41;
+
+/// Routes every address to the component that owns it
+pub struct Mmu {
+    cartridge: Option<Box<dyn Bus>>,
+
+    vram: Box<[u8; 0x2000]>,
+    wram: Box<[u8; 0x2000]>,
+    oam: Box<[u8; 0xA0]>,
+    io: Box<[u8; 0x80]>,
+    hram: Box<[u8; 0x7F]>,
+    interrupt_enable: u8,
+}
+
+impl Default for Mmu {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Mmu {
+    pub fn new() -> Self {
+        Mmu {
+            cartridge: None,
+            vram: Box::new([0; 0x2000]),
+            wram: Box::new([0; 0x2000]),
+            oam: Box::new([0; 0xA0]),
+            io: Box::new([0; 0x80]),
+            hram: Box::new([0; 0x7F]),
+            interrupt_enable: 0,
+        }
+    }
+
+    /// Plug a cartridge into [0000, 7FFF] and [A000, BFFF]
+    pub fn insert_cartridge(&mut self, cartridge: Box<dyn Bus>) {
+        self.cartridge = Some(cartridge);
+    }
+
+    pub fn remove_cartridge(&mut self) -> Option<Box<dyn Bus>> {
+        self.cartridge.take()
+    }
+
+    /// The inserted cartridge, if it is a T
+    pub fn cartridge<T: Bus>(&self) -> Option<&T> {
+        let cartridge: &dyn Any = self.cartridge.as_deref()?;
+        cartridge.downcast_ref()
+    }
+
+    pub fn cartridge_mut<T: Bus>(&mut self) -> Option<&mut T> {
+        let cartridge: &mut dyn Any = self.cartridge.as_deref_mut()?;
+        cartridge.downcast_mut()
+    }
+
+    fn read_io(&self, index: usize) -> u8 {
+        self.io[index] | IO_UNUSED_BITS[index]
+    }
+
+    /// DMG reads 0xFF while the PPU holds OAM (modes 2 and 3), 0x00 otherwise
+    fn read_prohibited(&self) -> u8 {
+        match self.io[LCD_STATUS] & 0x03 {
+            2 | 3 => 0xFF,
+            _ => 0x00,
+        }
+    }
+}
+
+impl Bus for Mmu {
+    fn read(&mut self, address: u16) -> u8 {
+        let index = address as usize;
+        match address {
+            0x0000..=0x7FFF | 0xA000..=0xBFFF => match &mut self.cartridge {
+                Some(cartridge) => cartridge.read(address),
+                // Nothing drives the bus
+                None => 0xFF,
+            },
+            0x8000..=0x9FFF => self.vram[index - 0x8000],
+            0xC000..=0xDFFF => self.wram[index - 0xC000],
+            0xE000..=0xFDFF => self.wram[index - 0xE000],
+            0xFE00..=0xFE9F => self.oam[index - 0xFE00],
+            0xFEA0..=0xFEFF => self.read_prohibited(),
+            0xFF00..=0xFF7F => self.read_io(index - 0xFF00),
+            0xFF80..=0xFFFE => self.hram[index - 0xFF80],
+            0xFFFF => self.interrupt_enable,
+        }
+    }
+
+    fn write(&mut self, address: u16, value: u8) {
+        let index = address as usize;
+        match address {
+            0x0000..=0x7FFF | 0xA000..=0xBFFF => {
+                if let Some(cartridge) = &mut self.cartridge {
+                    cartridge.write(address, value);
+                }
+            }
+            0x8000..=0x9FFF => self.vram[index - 0x8000] = value,
+            0xC000..=0xDFFF => self.wram[index - 0xC000] = value,
+            0xE000..=0xFDFF => self.wram[index - 0xE000] = value,
+            0xFE00..=0xFE9F => self.oam[index - 0xFE00] = value,
+            0xFEA0..=0xFEFF => (),
+            // Any write resets DIV, whatever the value
+            0xFF04 => self.io[DIVIDER] = 0,
+            0xFF00..=0xFF7F => self.io[index - 0xFF00] = value,
+            0xFF80..=0xFFFE => self.hram[index - 0xFF80] = value,
+            0xFFFF => self.interrupt_enable = value,
+        }
+    }
+
+    fn peek(&self, address: u16) -> u8 {
+        let index = address as usize;
+        match address {
+            0x0000..=0x7FFF | 0xA000..=0xBFFF => match &self.cartridge {
+                Some(cartridge) => cartridge.peek(address),
+                None => 0xFF,
+            },
+            0x8000..=0x9FFF => self.vram[index - 0x8000],
+            0xC000..=0xDFFF => self.wram[index - 0xC000],
+            0xE000..=0xFDFF => self.wram[index - 0xE000],
+            0xFE00..=0xFE9F => self.oam[index - 0xFE00],
+            0xFEA0..=0xFEFF => self.read_prohibited(),
+            0xFF00..=0xFF7F => self.read_io(index - 0xFF00),
+            0xFF80..=0xFFFE => self.hram[index - 0xFF80],
+            0xFFFF => self.interrupt_enable,
+        }
+    }
+
+    fn poke(&mut self, address: u16, value: u8) {
+        let index = address as usize;
+        match address {
+            0x0000..=0x7FFF | 0xA000..=0xBFFF => {
+                if let Some(cartridge) = &mut self.cartridge {
+                    cartridge.poke(address, value);
+                }
+            }
+            0x8000..=0x9FFF => self.vram[index - 0x8000] = value,
+            0xC000..=0xDFFF => self.wram[index - 0xC000] = value,
+            0xE000..=0xFDFF => self.wram[index - 0xE000] = value,
+            0xFE00..=0xFE9F => self.oam[index - 0xFE00] = value,
+            // Nothing backs it to store into
+            0xFEA0..=0xFEFF => (),
+            0xFF00..=0xFF7F => self.io[index - 0xFF00] = value,
+            0xFF80..=0xFFFE => self.hram[index - 0xFF80] = value,
+            0xFFFF => self.interrupt_enable = value,
+        }
+    }
+
+    fn tick(&mut self, cycles: u32) {
+        if let Some(cartridge) = &mut self.cartridge {
+            cartridge.tick(cycles);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    struct TestCartridge {
+        rom: [u8; 0x8000],
+    }
+
+    impl Bus for TestCartridge {
+        fn read(&mut self, address: u16) -> u8 {
+            match address {
+                0x0000..=0x7FFF => self.rom[address as usize],
+                _ => 0xFF,
+            }
+        }
+
+        fn write(&mut self, _address: u16, _value: u8) { }
+
+        fn peek(&self, address: u16) -> u8 {
+            match address {
+                0x0000..=0x7FFF => self.rom[address as usize],
+                _ => 0xFF,
+            }
+        }
+
+        fn poke(&mut self, address: u16, value: u8) {
+            if let 0x0000..=0x7FFF = address {
+                self.rom[address as usize] = value;
+            }
+        }
+    }
+
+    #[test]
+    fn ram_regions_work() {
+        let mut mmu = Mmu::new();
+
+        for address in [0x8000, 0x9FFF, 0xC000, 0xDFFF, 0xFE00, 0xFE9F, 0xFF80, 0xFFFE, 0xFFFF] {
+            mmu.write(address, 0xAB);
+            assert_eq!(mmu.read(address), 0xAB);
+        }
+    }
+
+    #[test]
+    fn echo_ram_mirrors_work_ram() {
+        let mut mmu = Mmu::new();
+
+        mmu.write(0xC123, 0xAB);
+        assert_eq!(mmu.read(0xE123), 0xAB);
+
+        mmu.write(0xFDFF, 0xCD);
+        assert_eq!(mmu.read(0xDDFF), 0xCD);
+    }
+
+    #[test]
+    fn prohibited_region_follows_ppu_mode() {
+        let mut mmu = Mmu::new();
+
+        mmu.write(0xFEA0, 0xAB);
+        assert_eq!(mmu.read(0xFEA0), 0x00);
+
+        mmu.write(0xFF41, 0x03);
+        assert_eq!(mmu.read(0xFEFF), 0xFF);
+
+        mmu.write(0xFF41, 0x01);
+        assert_eq!(mmu.peek(0xFEC0), 0x00);
+    }
+
+    #[test]
+    fn unused_io_bits_read_as_ones() {
+        let mut mmu = Mmu::new();
+
+        mmu.write(0xFF0F, 0x01);
+        assert_eq!(mmu.read(0xFF0F), 0xE1);
+
+        mmu.write(0xFF07, 0x05);
+        assert_eq!(mmu.read(0xFF07), 0xFD);
+
+        mmu.write(0xFF03, 0x00);
+        assert_eq!(mmu.read(0xFF03), 0xFF);
+        assert_eq!(mmu.read(0xFF4D), 0xFF);
+
+        mmu.write(0xFF42, 0xAB);
+        assert_eq!(mmu.peek(0xFF42), 0xAB);
+    }
+
+    #[test]
+    fn divider_resets_on_write() {
+        let mut mmu = Mmu::new();
+
+        mmu.poke(0xFF04, 0xAB);
+        assert_eq!(mmu.read(0xFF04), 0xAB);
+
+        mmu.write(0xFF04, 0x12);
+        assert_eq!(mmu.read(0xFF04), 0x00);
+    }
+
+    #[test]
+    fn cartridge_is_routed() {
+        let mut mmu = Mmu::new();
+        assert_eq!(mmu.read(0x0100), 0xFF);
+
+        let mut rom = [0; 0x8000];
+        rom[0x0100] = 0xAB;
+        rom[0x4000] = 0xCD;
+        mmu.insert_cartridge(Box::new(TestCartridge { rom }));
+
+        mmu.write(0x0100, 0x00);
+        assert_eq!(mmu.read(0x0100), 0xAB);
+        assert_eq!(mmu.read(0x4000), 0xCD);
+        assert_eq!(mmu.read(0xA000), 0xFF);
+    }
+
+    #[test]
+    fn cartridge_downcast_works() {
+        let mut mmu = Mmu::new();
+        assert!(mmu.cartridge::<TestCartridge>().is_none());
+
+        mmu.insert_cartridge(Box::new(TestCartridge { rom: [0; 0x8000] }));
+        mmu.cartridge_mut::<TestCartridge>().unwrap().rom[0x0100] = 0xAB;
+
+        assert_eq!(mmu.read(0x0100), 0xAB);
+        assert!(mmu.cartridge::<Mmu>().is_none());
+    }
+
+    #[test]
+    fn peek_matches_read() {
+        let mut mmu = Mmu::new();
+        let mut rom = [0; 0x8000];
+        rom[0x0150] = 0x12;
+        mmu.insert_cartridge(Box::new(TestCartridge { rom }));
+        mmu.write(0xC000, 0x34);
+        mmu.write(0xFF80, 0x56);
+
+        for address in [0x0150, 0xA000, 0xC000, 0xE000, 0xFF80, 0xFEA0] {
+            assert_eq!(mmu.peek(address), mmu.read(address));
+        }
+    }
+
+    #[test]
+    fn poke_patches_rom() {
+        let mut mmu = Mmu::new();
+        mmu.insert_cartridge(Box::new(TestCartridge { rom: [0; 0x8000] }));
+
+        mmu.write(0x2000, 0xAB);
+        assert_eq!(mmu.peek(0x2000), 0x00);
+
+        mmu.poke(0x2000, 0xAB);
+        assert_eq!(mmu.read(0x2000), 0xAB);
+
+        mmu.poke(0xD000, 0xCD);
+        assert_eq!(mmu.read(0xD000), 0xCD);
+    }
+
+    #[test]
+    fn poke_stores_raw_bytes() {
+        let mut mmu = Mmu::new();
+
+        mmu.poke(0xFF0F, 0x1F);
+        assert_eq!(mmu.io[0x0F], 0x1F);
+
+        mmu.poke(0xE123, 0xAB);
+        assert_eq!(mmu.peek(0xC123), 0xAB);
+
+        mmu.poke(0xFFFF, 0x1F);
+        assert_eq!(mmu.peek(0xFFFF), 0x1F);
+    }
+}
@@ -0,0 +1,872 @@
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::bus::Bus;
+
+mod camera;
+mod huc1;
+mod huc3;
+mod mbc1;
+mod mbc2;
+mod mbc3;
+mod mbc5;
+mod mbc7;
+mod mmm01;
+mod rom_only;
+
+use camera::PocketCamera;
+use huc1::HuC1;
+use huc3::HuC3;
+use mbc1::Mbc1;
+use mbc2::Mbc2;
+use mbc3::Mbc3;
+use mbc5::Mbc5;
+use mbc7::Mbc7;
+use mmm01::Mmm01;
+use rom_only::RomOnly;
+
+// Cartridge Header => [0100, 014F]
+// https://gbdev.io/pandocs/The_Cartridge_Header.html
+/* [0100, 0103] Entry Point
+ * [0104, 0133] Nintendo Logo - MUST HAVE
+ * [0134, 0143] Game Tittle - OLD Cartridges
+ * [013F, 0142] Manifacturer Code - NEWER Cartridges
+ * [0143] CGB Flag - NEWER Cartridges
+ * [0144, 0145] New Licensee Code
+ * [0146] SGB Flag
+ * [0147] Cartridge Type
+ * [0148] ROM size
+ * [0149] RAM size
+ * [014A] Destination code
+ * [014B] Old Licensee Code
+ * [014C] ROM version number
+ * [014D] Header Checksum
+ * [014E, 014F] Global Checksum
+ */
+
+pub const HEADER_END: usize = 0x0150;
+// T-cycles in one second, for the clocks on some cartridges
+const CLOCK_SPEED: u32 = 4_194_304;
+
+#[derive(Debug)]
+pub enum CartridgeError {
+    Io(io::Error),
+    // Length of the file, too short to hold [0100, 014F]
+    TooSmall(usize),
+    HeaderChecksum { expected: u8, computed: u8 },
+    GlobalChecksum { expected: u16, computed: u16 },
+    // Byte 0x148 disagrees with the length of the file
+    RomSize { code: u8, length: usize },
+    UnsupportedMapper(Mapper),
+    InvalidRamSize(u8),
+}
+
+impl fmt::Display for CartridgeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            CartridgeError::Io(why) => write!(f, "couldn't read cartridge: {}", why),
+            CartridgeError::TooSmall(length) => write!(f, "{} bytes is too small for a cartridge header", length),
+            CartridgeError::HeaderChecksum { expected, computed } =>
+                write!(f, "header checksum is {:#04X} but the header sums to {:#04X}", expected, computed),
+            CartridgeError::GlobalChecksum { expected, computed } =>
+                write!(f, "global checksum is {:#06X} but the rom sums to {:#06X}", expected, computed),
+            CartridgeError::RomSize { code, length } =>
+                write!(f, "rom size byte {:#04X} doesn't match a {} byte rom", code, length),
+            CartridgeError::UnsupportedMapper(mapper) => write!(f, "unsupported mapper {:?}", mapper),
+            CartridgeError::InvalidRamSize(code) => write!(f, "invalid ram size byte {:#04X}", code),
+        }
+    }
+}
+
+impl std::error::Error for CartridgeError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            CartridgeError::Io(why) => Some(why),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for CartridgeError {
+    fn from(why: io::Error) -> Self {
+        CartridgeError::Io(why)
+    }
+}
+
+/// How to treat a cartridge whose header disagrees with its contents
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum LoadMode {
+    // Refuse to load, returning the first problem
+    Strict,
+    // Load anyway, returning every problem as a warning
+    Lenient,
+}
+
+/// Chip that decides what is mapped into [0000, 7FFF] and [A000, BFFF]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Mapper {
+    RomOnly,
+    Mbc1,
+    Mbc2,
+    Mmm01,
+    Mbc3,
+    Mbc5,
+    Mbc6,
+    Mbc7,
+    PocketCamera,
+    Tama5,
+    HuC3,
+    HuC1,
+    Unknown(u8),
+}
+
+/// Byte 0x147 split into the mapper and the extra hardware on the board
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct CartridgeType {
+    pub mapper: Mapper,
+    pub ram: bool,
+    pub battery: bool,
+    pub timer: bool,
+    pub rumble: bool,
+    pub sensor: bool,
+}
+
+impl CartridgeType {
+    pub fn from_code(code: u8) -> CartridgeType {
+        let (mapper, ram, battery, timer, rumble, sensor) = match code {
+            0x00 => (Mapper::RomOnly, false, false, false, false, false),
+            0x01 => (Mapper::Mbc1, false, false, false, false, false),
+            0x02 => (Mapper::Mbc1, true, false, false, false, false),
+            0x03 => (Mapper::Mbc1, true, true, false, false, false),
+            0x05 => (Mapper::Mbc2, false, false, false, false, false),
+            0x06 => (Mapper::Mbc2, false, true, false, false, false),
+            0x08 => (Mapper::RomOnly, true, false, false, false, false),
+            0x09 => (Mapper::RomOnly, true, true, false, false, false),
+            0x0B => (Mapper::Mmm01, false, false, false, false, false),
+            0x0C => (Mapper::Mmm01, true, false, false, false, false),
+            0x0D => (Mapper::Mmm01, true, true, false, false, false),
+            0x0F => (Mapper::Mbc3, false, true, true, false, false),
+            0x10 => (Mapper::Mbc3, true, true, true, false, false),
+            0x11 => (Mapper::Mbc3, false, false, false, false, false),
+            0x12 => (Mapper::Mbc3, true, false, false, false, false),
+            0x13 => (Mapper::Mbc3, true, true, false, false, false),
+            0x19 => (Mapper::Mbc5, false, false, false, false, false),
+            0x1A => (Mapper::Mbc5, true, false, false, false, false),
+            0x1B => (Mapper::Mbc5, true, true, false, false, false),
+            0x1C => (Mapper::Mbc5, false, false, false, true, false),
+            0x1D => (Mapper::Mbc5, true, false, false, true, false),
+            0x1E => (Mapper::Mbc5, true, true, false, true, false),
+            0x20 => (Mapper::Mbc6, true, true, false, false, false),
+            0x22 => (Mapper::Mbc7, true, true, false, true, true),
+            0xFC => (Mapper::PocketCamera, true, true, false, false, true),
+            0xFD => (Mapper::Tama5, true, true, true, false, false),
+            0xFE => (Mapper::HuC3, true, true, true, false, false),
+            0xFF => (Mapper::HuC1, true, true, false, false, false),
+            _ => (Mapper::Unknown(code), false, false, false, false, false),
+        };
+
+        CartridgeType { mapper, ram, battery, timer, rumble, sensor }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum CgbFlag {
+    // DMG only, the byte is part of the title
+    None,
+    // Works on DMG, enhanced on CGB
+    Enhanced,
+    Only,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Destination {
+    Japan,
+    Overseas,
+}
+
+/// Everything in [0100, 014F] that describes the cartridge
+#[derive(Clone, Debug, PartialEq)]
+pub struct CartridgeHeader {
+    pub title: String,
+    // Only on newer cartridges, older ones use these bytes for the title
+    pub manufacturer_code: Option<String>,
+    pub cgb_flag: CgbFlag,
+    pub sgb_flag: bool,
+    pub cartridge_type: CartridgeType,
+    // None when the size byte is not a known code
+    pub rom_banks: Option<usize>,
+    pub ram_banks: Option<usize>,
+    pub destination: Destination,
+    pub old_licensee: u8,
+    pub new_licensee: String,
+    pub version: u8,
+    pub header_checksum: u8,
+    pub global_checksum: u16,
+}
+
+impl CartridgeHeader {
+    /// Parse the header out of a whole ROM image, which must hold at least [0000, 014F]
+    pub fn parse(bytes: &[u8]) -> Result<CartridgeHeader, CartridgeError> {
+        if bytes.len() < HEADER_END {
+            return Err(CartridgeError::TooSmall(bytes.len()));
+        }
+
+        let cgb_flag = match bytes[0x0143] {
+            0x80 => CgbFlag::Enhanced,
+            0xC0 => CgbFlag::Only,
+            _ => CgbFlag::None,
+        };
+
+        let (title, manufacturer_code) = match cgb_flag {
+            CgbFlag::None => (text(&bytes[0x0134..0x0144]), None),
+            _ => (text(&bytes[0x0134..0x013F]), Some(text(&bytes[0x013F..0x0143]))),
+        };
+
+        Ok(CartridgeHeader {
+            title,
+            manufacturer_code,
+            cgb_flag,
+            sgb_flag: bytes[0x0146] == 0x03,
+            cartridge_type: CartridgeType::from_code(bytes[0x0147]),
+            rom_banks: rom_banks(bytes[0x0148]),
+            ram_banks: ram_banks(bytes[0x0149]),
+            destination: match bytes[0x014A] {
+                0x00 => Destination::Japan,
+                _ => Destination::Overseas,
+            },
+            old_licensee: bytes[0x014B],
+            new_licensee: text(&bytes[0x0144..0x0146]),
+            version: bytes[0x014C],
+            header_checksum: bytes[0x014D],
+            global_checksum: u16::from_be_bytes([bytes[0x014E], bytes[0x014F]]),
+        })
+    }
+
+    /// Every way the rom disagrees with this header or the emulator
+    pub fn verify(&self, bytes: &[u8]) -> Vec<CartridgeError> {
+        // Nothing else can be checked without a whole header
+        if bytes.len() < HEADER_END {
+            return vec![CartridgeError::TooSmall(bytes.len())];
+        }
+
+        let mut problems = Vec::new();
+
+        let header = &bytes[CartridgeHeader::locate(bytes)..];
+
+        let computed = header_checksum(header);
+        if computed != self.header_checksum {
+            problems.push(CartridgeError::HeaderChecksum { expected: self.header_checksum, computed });
+        }
+
+        let computed = global_checksum(bytes);
+        if computed != self.global_checksum {
+            problems.push(CartridgeError::GlobalChecksum { expected: self.global_checksum, computed });
+        }
+
+        if self.rom_banks.map(|banks| banks * 0x4000) != Some(bytes.len()) {
+            problems.push(CartridgeError::RomSize { code: header[0x0148], length: bytes.len() });
+        }
+
+        if !Cartridge::supports(self.cartridge_type.mapper) {
+            problems.push(CartridgeError::UnsupportedMapper(self.cartridge_type.mapper));
+        }
+
+        if self.ram_banks.is_none() {
+            problems.push(CartridgeError::InvalidRamSize(header[0x0149]));
+        }
+
+        problems
+    }
+
+    /// Offset of the header in a rom image, MMM01 boots from the last 32 KiB and keeps it there
+    pub fn locate(bytes: &[u8]) -> usize {
+        let offset = bytes.len().saturating_sub(0x8000);
+        let last = &bytes[offset..];
+
+        if offset > 0 && last.len() >= HEADER_END
+            && matches!(last[0x0147], 0x0B..=0x0D)
+            && header_checksum(last) == last[0x014D] {
+            return offset;
+        }
+
+        0
+    }
+
+    /// Old licensee 0x33 means the new licensee code is used instead
+    pub fn uses_new_licensee(&self) -> bool {
+        self.old_licensee == 0x33
+    }
+}
+
+/// Checksum of [0134, 014C] the boot ROM compares against 0x014D
+pub fn header_checksum(bytes: &[u8]) -> u8 {
+    let mut sum: u8 = 0;
+    for byte in &bytes[0x0134..=0x014C] {
+        sum = sum.wrapping_sub(byte.wrapping_add(1));
+    }
+    sum
+}
+
+/// Sum of every byte but the checksum itself, not verified by the hardware
+pub fn global_checksum(bytes: &[u8]) -> u16 {
+    let mut sum: u16 = 0;
+    for (i, byte) in bytes.iter().enumerate() {
+        if i != 0x014E && i != 0x014F {
+            sum = sum.wrapping_add(*byte as u16);
+        }
+    }
+    sum
+}
+
+// 16 KiB banks
+fn rom_banks(code: u8) -> Option<usize> {
+    match code {
+        0x00..=0x08 => Some(2 << code),
+        0x52 => Some(72),
+        0x53 => Some(80),
+        0x54 => Some(96),
+        _ => None,
+    }
+}
+
+// 8 KiB banks
+fn ram_banks(code: u8) -> Option<usize> {
+    match code {
+        0x00 => Some(0),
+        // Unused, some homebrew use it for 2 KiB
+        0x01 => Some(1),
+        0x02 => Some(1),
+        0x03 => Some(4),
+        0x04 => Some(16),
+        0x05 => Some(8),
+        _ => None,
+    }
+}
+
+// Unix time in seconds, for clocks to catch up on the time the emulator was closed
+fn now() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map(|time| time.as_secs()).unwrap_or(0)
+}
+
+// Header strings are padded with zeros
+fn text(bytes: &[u8]) -> String {
+    let end = bytes.iter().position(|&byte| byte == 0).unwrap_or(bytes.len());
+    String::from_utf8_lossy(&bytes[..end]).into_owned()
+}
+
+/// Bank switching chip wired between the bus and the rom and ram chips
+pub trait Mbc {
+    /// Rom bank answering address in [0000, 7FFF], the cartridge wraps it to the rom size
+    fn rom_bank(&self, address: u16) -> usize;
+    /// Writes to [0000, 7FFF] land on the chip registers
+    fn write_register(&mut self, address: u16, value: u8);
+    fn read_ram(&self, ram: &[[u8; 0x2000]], address: u16) -> u8;
+    fn write_ram(&mut self, ram: &mut [[u8; 0x2000]], address: u16, value: u8);
+    /// Ram bank and offset a write to address in [A000, BFFF] lands on, enabled or not,
+    /// None when no RAM is mapped there
+    fn ram_location(&self, ram: &[[u8; 0x2000]], address: u16) -> Option<(usize, usize)>;
+
+    /// Let cycles of the CPU clock go by
+    fn tick(&mut self, _cycles: u32) { }
+
+    /// Whether the rumble motor is spinning
+    fn rumble(&self) -> bool {
+        false
+    }
+
+    /// Feed the accelerometer, in g
+    fn set_tilt(&mut self, _x: f32, _y: f32) { }
+
+    /// Feed the image sensor, 128 x 112 greyscale with 0 as black
+    fn set_image(&mut self, _image: Vec<u8>) { }
+
+    /// Whether the infrared LED is lit
+    fn infrared(&self) -> bool {
+        false
+    }
+
+    /// Whether the infrared sensor sees light
+    fn receive_infrared(&mut self, _light: bool) { }
+
+    /// Whether the speaker is sounding the alarm tone
+    fn tone(&self) -> bool {
+        false
+    }
+
+    /// What a battery keeps alive between runs
+    fn save(&self, ram: &[[u8; 0x2000]]) -> Vec<u8> {
+        ram.iter().flatten().copied().collect()
+    }
+
+    fn load(&mut self, ram: &mut [[u8; 0x2000]], data: &[u8]) {
+        for (byte, value) in ram.iter_mut().flatten().zip(data) {
+            *byte = *value;
+        }
+    }
+}
+
+pub struct Cartridge {
+    rom: Box<[[u8; 0x4000]]>,
+    ram: Box<[[u8; 0x2000]]>,
+    rom_banks: usize,
+    ram_banks: usize,
+    battery: bool,
+    mbc: Box<dyn Mbc>,
+    rumble_listener: Option<Box<dyn FnMut(bool)>>,
+    infrared_listener: Option<Box<dyn FnMut(bool)>>,
+}
+
+impl Cartridge {
+    pub(crate) fn new(rom_banks: usize, ram_banks: usize) -> Cartridge {
+        assert!(rom_banks >= 2, "rom size too low");
+        assert!(rom_banks <= 512, "out of bounds rom size");
+        assert!(ram_banks <= 16, "out of bounds ram size");
+
+        Cartridge {
+            rom: vec![[0; 0x4000]; rom_banks].into_boxed_slice(),
+            ram: vec![[0; 0x2000]; ram_banks].into_boxed_slice(),
+            rom_banks,
+            ram_banks,
+            battery: false,
+            mbc: Box::new(RomOnly),
+            rumble_listener: None,
+            infrared_listener: None,
+        }
+    }
+
+    /// Load a rom file, returning the problems Lenient mode let through
+    pub fn open(path: &str, mode: LoadMode) -> Result<(Cartridge, Vec<CartridgeError>), CartridgeError> {
+        let bytes = fs::read(Path::new(path))?;
+        Cartridge::from_bytes(bytes, mode)
+    }
+
+    /// Build a cartridge from a whole rom image, returning the problems Lenient mode let through
+    pub fn from_bytes(bytes: Vec<u8>, mode: LoadMode) -> Result<(Cartridge, Vec<CartridgeError>), CartridgeError> {
+        let header = CartridgeHeader::parse(&bytes[CartridgeHeader::locate(&bytes)..])?;
+
+        let mut problems = header.verify(&bytes);
+        if mode == LoadMode::Strict && !problems.is_empty() {
+            return Err(problems.remove(0));
+        }
+
+        // Room for both the data and what the header claims, anything past 8 MiB is dropped
+        let rom_banks = bytes.len().div_ceil(0x4000)
+            .max(header.rom_banks.unwrap_or(2))
+            .clamp(2, 512);
+        let ram_banks = match header.cartridge_type.mapper {
+            // Built into the controller or an EEPROM, the header says no RAM
+            Mapper::Mbc2 | Mapper::Mbc7 => 1,
+            // Captures land in the first bank, always fitted with 128 KiB
+            Mapper::PocketCamera => 16,
+            _ => header.ram_banks.unwrap_or(0),
+        };
+
+        let mut cartridge = Cartridge::new(rom_banks, ram_banks);
+        for (bank, data) in cartridge.rom.iter_mut().zip(bytes.chunks(0x4000)) {
+            bank[..data.len()].copy_from_slice(data);
+        }
+
+        let cartridge_type = header.cartridge_type;
+        cartridge.battery = cartridge_type.battery;
+        cartridge.mbc = match cartridge_type.mapper {
+            Mapper::RomOnly => Box::new(RomOnly),
+            Mapper::Mbc1 => Box::new(Mbc1::new(Mbc1::is_multicart(&bytes))),
+            Mapper::Mbc2 => Box::new(Mbc2::new()),
+            Mapper::Mbc3 => Box::new(Mbc3::new(cartridge_type.timer)),
+            Mapper::Mbc5 => Box::new(Mbc5::new(cartridge_type.rumble)),
+            Mapper::Mbc7 => Box::new(Mbc7::new()),
+            Mapper::Mmm01 => Box::new(Mmm01::new()),
+            Mapper::HuC1 => Box::new(HuC1::new()),
+            Mapper::HuC3 => Box::new(HuC3::new()),
+            Mapper::PocketCamera => Box::new(PocketCamera::new()),
+            // Only gets here in Lenient mode, plain ROM is the best guess
+            _ => Box::new(RomOnly),
+        };
+
+        Ok((cartridge, problems))
+    }
+
+    pub fn supports(mapper: Mapper) -> bool {
+        matches!(
+            mapper,
+            Mapper::RomOnly
+                | Mapper::Mbc1
+                | Mapper::Mbc2
+                | Mapper::Mbc3
+                | Mapper::Mbc5
+                | Mapper::Mbc7
+                | Mapper::Mmm01
+                | Mapper::HuC1
+                | Mapper::HuC3
+                | Mapper::PocketCamera
+        )
+    }
+
+    /// Battery backed memory to persist between runs, None without a battery
+    pub fn save_data(&self) -> Option<Vec<u8>> {
+        if !self.battery {
+            return None;
+        }
+
+        Some(self.mbc.save(&self.ram))
+    }
+
+    /// Restore what save_data returned on a previous run
+    pub fn load_save_data(&mut self, data: &[u8]) {
+        self.mbc.load(&mut self.ram, data);
+    }
+
+    /// Call listener with the new state whenever the rumble motor turns on or off
+    pub fn on_rumble(&mut self, listener: impl FnMut(bool) + 'static) {
+        self.rumble_listener = Some(Box::new(listener));
+    }
+
+    pub fn rumble(&self) -> bool {
+        self.mbc.rumble()
+    }
+
+    /// Call listener with the new state whenever the infrared LED turns on or off
+    pub fn on_infrared(&mut self, listener: impl FnMut(bool) + 'static) {
+        self.infrared_listener = Some(Box::new(listener));
+    }
+
+    pub fn infrared(&self) -> bool {
+        self.mbc.infrared()
+    }
+
+    /// Light reaching the infrared sensor, from another Game Boy or a remote
+    pub fn receive_infrared(&mut self, light: bool) {
+        self.mbc.receive_infrared(light);
+    }
+
+    pub fn tone(&self) -> bool {
+        self.mbc.tone()
+    }
+
+    /// Tilt of the cartridge in g, x positive to the right and y positive towards the bottom
+    pub fn set_tilt(&mut self, x: f32, y: f32) {
+        self.mbc.set_tilt(x, y);
+    }
+
+    /// What the camera sensor sees, greyscale with 0 as black, scaled to fit
+    pub fn set_camera_image(&mut self, width: usize, height: usize, pixels: &[u8]) {
+        self.mbc.set_image(camera::resample(width, height, pixels));
+    }
+
+    /// Load what the camera sensor sees from a greyscale PGM file
+    pub fn load_camera_image(&mut self, path: &str) -> Result<(), CartridgeError> {
+        let (width, height, pixels) = camera::parse_pgm(&fs::read(Path::new(path))?)?;
+        self.set_camera_image(width, height, &pixels);
+        Ok(())
+    }
+
+    fn rom_bank(&self, address: u16) -> usize {
+        self.mbc.rom_bank(address) % self.rom_banks
+    }
+}
+
+impl Bus for Cartridge {
+    fn read(&mut self, address: u16) -> u8 {
+        self.peek(address)
+    }
+
+    fn write(&mut self, address: u16, value: u8) {
+        let rumble = self.mbc.rumble();
+        let infrared = self.mbc.infrared();
+
+        match address {
+            0x0000..=0x7FFF => self.mbc.write_register(address, value),
+            0xA000..=0xBFFF => self.mbc.write_ram(&mut self.ram, address, value),
+            _ => (),
+        }
+
+        if self.mbc.rumble() != rumble {
+            if let Some(listener) = &mut self.rumble_listener {
+                listener(!rumble);
+            }
+        }
+
+        if self.mbc.infrared() != infrared {
+            if let Some(listener) = &mut self.infrared_listener {
+                listener(!infrared);
+            }
+        }
+    }
+
+    fn peek(&self, address: u16) -> u8 {
+        match address {
+            0x0000..=0x7FFF => self.rom[self.rom_bank(address)][address as usize & 0x3FFF],
+            0xA000..=0xBFFF => self.mbc.read_ram(&self.ram, address),
+            _ => 0xFF,
+        }
+    }
+
+    fn poke(&mut self, address: u16, value: u8) {
+        match address {
+            0x0000..=0x7FFF => {
+                let bank = self.rom_bank(address);
+                self.rom[bank][address as usize & 0x3FFF] = value;
+            }
+            0xA000..=0xBFFF => {
+                // Straight into the mapped bank, leaving the chip alone
+                if let Some((bank, offset)) = self.mbc.ram_location(&self.ram, address) {
+                    self.ram[bank][offset] = value;
+                }
+            }
+            _ => (),
+        }
+    }
+
+    fn tick(&mut self, cycles: u32) {
+        self.mbc.tick(cycles);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    use super::*;
+
+    fn header_bytes() -> Vec<u8> {
+        let mut bytes = vec![0; 0x8000];
+        bytes[0x0134..0x013D].copy_from_slice(b"TEST GAME");
+        bytes[0x0144..0x0146].copy_from_slice(b"01");
+        bytes[0x0146] = 0x03;
+        bytes[0x0147] = 0x1B;
+        bytes[0x0148] = 0x01;
+        bytes[0x0149] = 0x03;
+        bytes[0x014A] = 0x01;
+        bytes[0x014B] = 0x33;
+        bytes[0x014C] = 0x02;
+        bytes[0x014D] = header_checksum(&bytes);
+        bytes
+    }
+
+    /// Cartridge driven by mbc with every rom bank numbered in its first two bytes
+    pub(super) fn test_cartridge(rom_banks: usize, ram_banks: usize, mbc: impl Mbc + 'static) -> Cartridge {
+        let mut cartridge = Cartridge::new(rom_banks, ram_banks);
+        for (number, bank) in cartridge.rom.iter_mut().enumerate() {
+            bank[0] = number as u8;
+            bank[1] = (number >> 8) as u8;
+        }
+        cartridge.mbc = Box::new(mbc);
+        cartridge
+    }
+
+    /// Peek and poke have to reach the byte a normal read and write do, with RAM enabled
+    pub(super) fn assert_poke_matches_write(cartridge: &mut Cartridge, address: u16) {
+        for value in [0x5A, 0xA5] {
+            cartridge.write(address, value);
+            let written = cartridge.read(address);
+            assert_eq!(cartridge.peek(address), written);
+
+            cartridge.write(address, !value);
+            cartridge.poke(address, value);
+            assert_eq!(cartridge.read(address), written);
+        }
+    }
+
+    /// Listener keeping every state it was called with, for the rumble and infrared tests
+    pub(super) fn recorder() -> (Rc<RefCell<Vec<bool>>>, impl FnMut(bool)) {
+        let events = Rc::new(RefCell::new(Vec::new()));
+        let listener = events.clone();
+        (events, move |on| listener.borrow_mut().push(on))
+    }
+
+    #[test]
+    fn header_parse_works() {
+        let header = CartridgeHeader::parse(&header_bytes()).unwrap();
+
+        assert_eq!(header.title, "TEST GAME");
+        assert_eq!(header.manufacturer_code, None);
+        assert_eq!(header.cgb_flag, CgbFlag::None);
+        assert!(header.sgb_flag);
+        assert_eq!(header.cartridge_type.mapper, Mapper::Mbc5);
+        assert!(header.cartridge_type.ram);
+        assert!(header.cartridge_type.battery);
+        assert!(!header.cartridge_type.rumble);
+        assert_eq!(header.rom_banks, Some(4));
+        assert_eq!(header.ram_banks, Some(4));
+        assert_eq!(header.destination, Destination::Overseas);
+        assert!(header.uses_new_licensee());
+        assert_eq!(header.new_licensee, "01");
+        assert_eq!(header.version, 0x02);
+    }
+
+    #[test]
+    fn cgb_header_splits_manufacturer_code() {
+        let mut bytes = header_bytes();
+        bytes[0x013F..0x0143].copy_from_slice(b"ABCD");
+        bytes[0x0143] = 0xC0;
+
+        let header = CartridgeHeader::parse(&bytes).unwrap();
+
+        assert_eq!(header.title, "TEST GAME");
+        assert_eq!(header.manufacturer_code.as_deref(), Some("ABCD"));
+        assert_eq!(header.cgb_flag, CgbFlag::Only);
+    }
+
+    #[test]
+    fn checksums_work() {
+        let mut bytes = header_bytes();
+        assert_eq!(CartridgeHeader::parse(&bytes).unwrap().header_checksum, header_checksum(&bytes));
+
+        bytes[0x014E] = 0xAB;
+        bytes[0x014F] = 0xCD;
+        let sum = bytes.iter().map(|&byte| byte as u16).sum::<u16>() - 0xAB - 0xCD;
+        assert_eq!(global_checksum(&bytes), sum);
+        assert_eq!(CartridgeHeader::parse(&bytes).unwrap().global_checksum, 0xABCD);
+    }
+
+    #[test]
+    fn cartridge_type_features_work() {
+        let camera = CartridgeType::from_code(0xFC);
+        assert_eq!(camera.mapper, Mapper::PocketCamera);
+
+        let mbc3 = CartridgeType::from_code(0x10);
+        assert_eq!(mbc3.mapper, Mapper::Mbc3);
+        assert!(mbc3.timer && mbc3.ram && mbc3.battery);
+
+        assert_eq!(CartridgeType::from_code(0x04).mapper, Mapper::Unknown(0x04));
+    }
+
+    #[test]
+    fn verify_reports_every_problem() {
+        let mut bytes = header_bytes();
+        bytes[0x0147] = 0x00;
+        bytes[0x0149] = 0x00;
+        bytes[0x014D] = header_checksum(&bytes);
+        bytes.resize(0x10000, 0);
+        let [high, low] = global_checksum(&bytes).to_be_bytes();
+        bytes[0x014E] = high;
+        bytes[0x014F] = low;
+
+        let header = CartridgeHeader::parse(&bytes).unwrap();
+        assert!(header.verify(&bytes).is_empty());
+
+        bytes[0x0147] = 0x04;
+        bytes[0x0149] = 0x09;
+        bytes.truncate(0x8000);
+
+        let header = CartridgeHeader::parse(&bytes).unwrap();
+        let problems = header.verify(&bytes);
+
+        assert_eq!(problems.len(), 5);
+        assert!(matches!(problems[0], CartridgeError::HeaderChecksum { .. }));
+        assert!(matches!(problems[1], CartridgeError::GlobalChecksum { .. }));
+        assert!(matches!(problems[2], CartridgeError::RomSize { code: 0x01, length: 0x8000 }));
+        assert!(matches!(problems[3], CartridgeError::UnsupportedMapper(Mapper::Unknown(0x04))));
+        assert!(matches!(problems[4], CartridgeError::InvalidRamSize(0x09)));
+    }
+
+    #[test]
+    fn verify_rejects_truncated_rom() {
+        let bytes = header_bytes();
+        let header = CartridgeHeader::parse(&bytes).unwrap();
+
+        let problems = header.verify(&bytes[..0x0140]);
+        assert_eq!(problems.len(), 1);
+        assert!(matches!(problems[0], CartridgeError::TooSmall(0x0140)));
+    }
+
+    #[test]
+    fn from_bytes_sizes_rom() {
+        let mut bytes = header_bytes();
+        bytes[0x0147] = 0x00;
+        bytes[0x0148] = 0x00;
+        bytes[0x0149] = 0x00;
+        bytes[0x014D] = header_checksum(&bytes);
+        bytes[0x7FFF] = 0xAB;
+
+        let (mut cartridge, warnings) = Cartridge::from_bytes(bytes.clone(), LoadMode::Lenient).unwrap();
+        assert_eq!(cartridge.rom_banks, 2);
+        assert_eq!(cartridge.read(0x7FFF), 0xAB);
+        assert_eq!(warnings.len(), 1);
+
+        assert!(matches!(
+            Cartridge::from_bytes(bytes.clone(), LoadMode::Strict),
+            Err(CartridgeError::GlobalChecksum { .. })
+        ));
+
+        // Header claims 4 banks but only 3 were dumped
+        bytes[0x0148] = 0x01;
+        bytes.resize(0xC000, 0xCD);
+        let (cartridge, _) = Cartridge::from_bytes(bytes, LoadMode::Lenient).unwrap();
+        assert_eq!(cartridge.rom_banks, 4);
+        assert_eq!(cartridge.rom[2][0], 0xCD);
+    }
+
+    fn rom_ram_bytes(code: u8) -> Vec<u8> {
+        let mut bytes = header_bytes();
+        bytes[0x0147] = code;
+        bytes[0x0148] = 0x00;
+        bytes[0x0149] = 0x02;
+        bytes[0x014D] = header_checksum(&bytes);
+        bytes
+    }
+
+    #[test]
+    fn rom_only_maps_both_banks() {
+        let mut bytes = rom_ram_bytes(0x00);
+        bytes[0x0000] = 0x12;
+        bytes[0x3FFF] = 0x34;
+        bytes[0x4000] = 0x56;
+        bytes[0x7FFF] = 0x78;
+
+        let (mut cartridge, _) = Cartridge::from_bytes(bytes, LoadMode::Lenient).unwrap();
+
+        cartridge.write(0x2000, 0x02);
+        cartridge.write(0x4000, 0x00);
+
+        assert_eq!(cartridge.read(0x0000), 0x12);
+        assert_eq!(cartridge.read(0x3FFF), 0x34);
+        assert_eq!(cartridge.read(0x4000), 0x56);
+        assert_eq!(cartridge.read(0x7FFF), 0x78);
+    }
+
+    #[test]
+    fn rom_ram_battery_works() {
+        let (mut cartridge, _) = Cartridge::from_bytes(rom_ram_bytes(0x09), LoadMode::Lenient).unwrap();
+
+        cartridge.write(0xA000, 0xAB);
+        cartridge.write(0xBFFF, 0xCD);
+        assert_eq!(cartridge.read(0xA000), 0xAB);
+        assert_eq!(cartridge.read(0xBFFF), 0xCD);
+
+        let save = cartridge.save_data().unwrap();
+        assert_eq!(save.len(), 0x2000);
+
+        let (mut cartridge, _) = Cartridge::from_bytes(rom_ram_bytes(0x09), LoadMode::Lenient).unwrap();
+        cartridge.load_save_data(&save);
+        assert_eq!(cartridge.read(0xBFFF), 0xCD);
+
+        let (cartridge, _) = Cartridge::from_bytes(rom_ram_bytes(0x08), LoadMode::Lenient).unwrap();
+        assert_eq!(cartridge.save_data(), None);
+    }
+
+    #[test]
+    fn poke_matches_write() {
+        let mut cartridge = test_cartridge(2, 1, RomOnly);
+        assert_poke_matches_write(&mut cartridge, 0xA000);
+        assert_poke_matches_write(&mut cartridge, 0xBFFF);
+
+        cartridge.poke(0x4001, 0xAB);
+        assert_eq!(cartridge.read(0x4001), 0xAB);
+
+        // Nothing to land on without RAM
+        let mut cartridge = test_cartridge(2, 0, RomOnly);
+        cartridge.poke(0xA000, 0xAB);
+        assert_eq!(cartridge.peek(0xA000), 0xFF);
+    }
+
+    #[test]
+    fn load_errors_work() {
+        assert!(matches!(CartridgeHeader::parse(&[0; 0x100]), Err(CartridgeError::TooSmall(0x100))));
+        assert!(matches!(Cartridge::open("missing.gb", LoadMode::Lenient), Err(CartridgeError::Io(_))));
+    }
+}
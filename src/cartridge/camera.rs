@@ -0,0 +1,438 @@
+use std::io;
+
+use crate::cartridge::Mbc;
+
+// https://gbdev.io/pandocs/Gameboy_Camera.html
+/* [0000, 1FFF] RAM Write Enable - 0x0A enables, reads always work
+ * [2000, 3FFF] ROM Bank Number - 6 bits
+ * [4000, 5FFF] RAM Bank Number - 4 bits, bit 4 maps the sensor registers instead
+ */
+
+/* M64282FP registers, mirrored every 0x80 bytes
+ * [A000] bit 0 Capture start and busy, bits 1~2 sensor output mode
+ * [A001] bit 7 N, bits 5~6 VH edge mode, bits 0~4 Gain
+ * [A002, A003] Exposure time, MSB first, 0x1000 is nominal
+ * [A004] bits 4~6 Edge enhancement ratio, bit 3 Invert, bits 0~2 Output reference voltage
+ * [A005] Zero point and offset voltage
+ * [A006, A035] 4x4 dithering matrix with 3 thresholds per pixel
+ */
+
+pub const IMAGE_WIDTH: usize = 128;
+pub const IMAGE_HEIGHT: usize = 112;
+// Where the captured tiles go in RAM bank 0
+const IMAGE_ADDRESS: usize = 0x0100;
+const REGISTERS: usize = 0x36;
+const DITHER_MATRIX: usize = 0x06;
+const EDGE_RATIOS: [f32; 8] = [0.5, 0.75, 1.0, 1.25, 2.0, 3.0, 4.0, 5.0];
+
+pub struct PocketCamera {
+    ram_enabled: bool,
+    rom_bank: u8,
+    ram_bank: u8,
+    registers_mapped: bool,
+    registers: [u8; REGISTERS],
+    // What the lens sees, IMAGE_WIDTH x IMAGE_HEIGHT greyscale with 0 as black
+    image: Vec<u8>,
+    // T-cycles until the capture is done
+    busy: u32,
+}
+
+impl PocketCamera {
+    pub fn new() -> Self {
+        PocketCamera {
+            ram_enabled: false,
+            rom_bank: 1,
+            ram_bank: 0,
+            registers_mapped: false,
+            registers: [0; REGISTERS],
+            image: vec![0; IMAGE_WIDTH * IMAGE_HEIGHT],
+            busy: 0,
+        }
+    }
+
+    fn write_sensor(&mut self, ram: &mut [[u8; 0x2000]], register: usize, value: u8) {
+        match register {
+            0x00 => {
+                self.registers[0] = value & 0x07;
+                if value & 0x01 != 0 && self.busy == 0 {
+                    self.capture(ram);
+                }
+            }
+            0x01..=0x35 => self.registers[register] = value,
+            _ => (),
+        }
+    }
+
+    fn exposure(&self) -> u16 {
+        u16::from_be_bytes([self.registers[2], self.registers[3]])
+    }
+
+    // Sensor output for every pixel, before edge enhancement
+    fn levels(&self) -> Box<[f32; IMAGE_WIDTH * IMAGE_HEIGHT]> {
+        let gain = 1.0 + (self.registers[1] & 0x1F) as f32 / 8.0;
+        let exposure = self.exposure() as f32 / 0x1000 as f32;
+        let invert = self.registers[4] & 0x08 != 0;
+
+        let mut levels = Box::new([0.0; IMAGE_WIDTH * IMAGE_HEIGHT]);
+        for (level, &pixel) in levels.iter_mut().zip(&self.image) {
+            *level = (pixel as f32 * gain * exposure).min(255.0);
+            if invert {
+                *level = 255.0 - *level;
+            }
+        }
+
+        levels
+    }
+
+    // Edge enhancement then dithering down to the 2 bit colour, 3 being black
+    fn pixel(&self, levels: &[f32; IMAGE_WIDTH * IMAGE_HEIGHT], x: usize, y: usize) -> u8 {
+        let at = |x: usize, y: usize| levels[y * IMAGE_WIDTH + x];
+        let ratio = EDGE_RATIOS[(self.registers[4] >> 4) as usize & 0x07];
+        let level = at(x, y);
+
+        // VH picks horizontal, vertical or both, the edges repeat the border pixel
+        let mut edge = 0.0;
+        if self.registers[1] & 0x20 != 0 {
+            let (left, right) = (x.saturating_sub(1), (x + 1).min(IMAGE_WIDTH - 1));
+            edge += 2.0 * level - at(left, y) - at(right, y);
+        }
+        if self.registers[1] & 0x40 != 0 {
+            let (up, down) = (y.saturating_sub(1), (y + 1).min(IMAGE_HEIGHT - 1));
+            edge += 2.0 * level - at(x, up) - at(x, down);
+        }
+        let level = level + ratio * edge;
+
+        let pattern = DITHER_MATRIX + ((y % 4) * 4 + x % 4) * 3;
+        let thresholds = &self.registers[pattern..pattern + 3];
+        match level {
+            _ if level < thresholds[0] as f32 => 3,
+            _ if level < thresholds[1] as f32 => 2,
+            _ if level < thresholds[2] as f32 => 1,
+            _ => 0,
+        }
+    }
+
+    /// Run the image through the sensor and write it as 16 x 14 tiles
+    fn capture(&mut self, ram: &mut [[u8; 0x2000]]) {
+        let levels = self.levels();
+        for y in 0..IMAGE_HEIGHT {
+            for x in (0..IMAGE_WIDTH).step_by(8) {
+                let (mut low, mut high) = (0, 0);
+                for bit in 0..8 {
+                    let color = self.pixel(&levels, x + bit, y);
+                    low = low << 1 | (color & 0x01);
+                    high = high << 1 | (color >> 1);
+                }
+
+                let tile = (y / 8) * (IMAGE_WIDTH / 8) + x / 8;
+                let index = IMAGE_ADDRESS + tile * 16 + (y % 8) * 2;
+                ram[0][index] = low;
+                ram[0][index + 1] = high;
+            }
+        }
+
+        // Capture time is in 1 MiHz steps, 4 T-cycles each
+        let mode = if self.registers[1] & 0x80 != 0 { 0 } else { 512 };
+        self.busy = (32_446 + mode + 16 * self.exposure() as u32) * 4;
+    }
+}
+
+impl Mbc for PocketCamera {
+    fn rom_bank(&self, address: u16) -> usize {
+        match address {
+            0x0000..=0x3FFF => 0,
+            _ => self.rom_bank as usize,
+        }
+    }
+
+    fn write_register(&mut self, address: u16, value: u8) {
+        match address {
+            0x0000..=0x1FFF => self.ram_enabled = value & 0x0F == 0x0A,
+            0x2000..=0x3FFF => self.rom_bank = value & 0x3F,
+            0x4000..=0x5FFF => {
+                self.registers_mapped = value & 0x10 != 0;
+                self.ram_bank = value & 0x0F;
+            }
+            _ => (),
+        }
+    }
+
+    fn read_ram(&self, ram: &[[u8; 0x2000]], address: u16) -> u8 {
+        if self.registers_mapped {
+            // Only the capture register reads back
+            return match address & 0x7F {
+                0x00 => self.registers[0] & 0x06 | (self.busy > 0) as u8,
+                _ => 0x00,
+            };
+        }
+
+        // The sensor holds the RAM while capturing
+        if self.busy > 0 {
+            return 0x00;
+        }
+
+        self.ram_location(ram, address).map_or(0xFF, |(bank, offset)| ram[bank][offset])
+    }
+
+    fn write_ram(&mut self, ram: &mut [[u8; 0x2000]], address: u16, value: u8) {
+        if self.registers_mapped {
+            self.write_sensor(ram, (address & 0x7F) as usize, value);
+        } else if self.ram_enabled && self.busy == 0 {
+            if let Some((bank, offset)) = self.ram_location(ram, address) {
+                ram[bank][offset] = value;
+            }
+        }
+    }
+
+    fn ram_location(&self, ram: &[[u8; 0x2000]], address: u16) -> Option<(usize, usize)> {
+        if self.registers_mapped || ram.is_empty() {
+            return None;
+        }
+
+        Some((self.ram_bank as usize % ram.len(), address as usize & 0x1FFF))
+    }
+
+    fn tick(&mut self, cycles: u32) {
+        self.busy = self.busy.saturating_sub(cycles);
+    }
+
+    fn set_image(&mut self, image: Vec<u8>) {
+        self.image = image;
+    }
+}
+
+/// Scale any greyscale image to the sensor size, nearest neighbour
+pub fn resample(width: usize, height: usize, pixels: &[u8]) -> Vec<u8> {
+    let mut image = vec![0; IMAGE_WIDTH * IMAGE_HEIGHT];
+    if width == 0 || height == 0 {
+        return image;
+    }
+
+    for y in 0..IMAGE_HEIGHT {
+        for x in 0..IMAGE_WIDTH {
+            let source = (y * height / IMAGE_HEIGHT) * width + x * width / IMAGE_WIDTH;
+            image[y * IMAGE_WIDTH + x] = pixels.get(source).copied().unwrap_or(0);
+        }
+    }
+
+    image
+}
+
+/// Parse a binary (P5) or plain (P2) greyscale PGM into width, height and 8 bit pixels
+pub fn parse_pgm(bytes: &[u8]) -> io::Result<(usize, usize, Vec<u8>)> {
+    let invalid = |why: &str| io::Error::new(io::ErrorKind::InvalidData, format!("bad pgm: {}", why));
+
+    // Magic, width, height and maximum, separated by whitespace and comments
+    let mut fields = Vec::new();
+    let mut position = 0;
+    while fields.len() < 4 {
+        while position < bytes.len() && (bytes[position].is_ascii_whitespace() || bytes[position] == b'#') {
+            if bytes[position] == b'#' {
+                while position < bytes.len() && bytes[position] != b'\n' {
+                    position += 1;
+                }
+            } else {
+                position += 1;
+            }
+        }
+
+        let start = position;
+        while position < bytes.len() && !bytes[position].is_ascii_whitespace() {
+            position += 1;
+        }
+        if start == position {
+            return Err(invalid("truncated header"));
+        }
+        fields.push(String::from_utf8_lossy(&bytes[start..position]).into_owned());
+    }
+
+    let number = |field: &str| field.parse::<usize>().map_err(|_| invalid("expected a number"));
+    let (width, height, maximum) = (number(&fields[1])?, number(&fields[2])?, number(&fields[3])?);
+    if maximum == 0 || maximum > 255 {
+        return Err(invalid("only 8 bit images are supported"));
+    }
+
+    let values: Vec<usize> = match fields[0].as_str() {
+        // Exactly one whitespace byte after the header
+        "P5" => bytes.get(position + 1..).unwrap_or(&[]).iter().map(|&byte| byte as usize).collect(),
+        "P2" => String::from_utf8_lossy(&bytes[position..])
+            .split_ascii_whitespace()
+            .map(number)
+            .collect::<io::Result<_>>()?,
+        _ => return Err(invalid("not a greyscale image")),
+    };
+
+    let size = width.checked_mul(height).ok_or_else(|| invalid("image too large"))?;
+    if values.len() < size {
+        return Err(invalid("truncated pixels"));
+    }
+    if values[..size].iter().any(|&value| value > maximum) {
+        return Err(invalid("sample above the maximum"));
+    }
+
+    let pixels = values[..size].iter().map(|&value| (value * 255 / maximum) as u8).collect();
+    Ok((width, height, pixels))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::bus::Bus;
+    use crate::cartridge::test::{assert_poke_matches_write, test_cartridge};
+    use crate::cartridge::Cartridge;
+
+    fn cartridge() -> Cartridge {
+        let mut cartridge = test_cartridge(64, 16, PocketCamera::new());
+        cartridge.battery = true;
+        cartridge
+    }
+
+    fn write_sensor(cartridge: &mut Cartridge, register: u16, value: u8) {
+        cartridge.write(0x4000, 0x10);
+        cartridge.write(0xA000 + register, value);
+    }
+
+    // Thresholds at 0x40, 0x80 and 0xC0 everywhere
+    fn flat_matrix(cartridge: &mut Cartridge) {
+        for pixel in 0..16 {
+            write_sensor(cartridge, 0x06 + pixel * 3, 0x40);
+            write_sensor(cartridge, 0x07 + pixel * 3, 0x80);
+            write_sensor(cartridge, 0x08 + pixel * 3, 0xC0);
+        }
+    }
+
+    fn capture(cartridge: &mut Cartridge) {
+        write_sensor(cartridge, 0x00, 0x01);
+        while cartridge.read(0xA000) & 0x01 != 0 {
+            cartridge.tick(0x1000);
+        }
+        cartridge.write(0x4000, 0x00);
+    }
+
+    #[test]
+    fn banking_works() {
+        let mut cartridge = cartridge();
+
+        cartridge.write(0xA000, 0xAB);
+        assert_eq!(cartridge.read(0xA000), 0x00);
+
+        cartridge.write(0x0000, 0x0A);
+        cartridge.write(0x4000, 0x0F);
+        cartridge.write(0xA000, 0xAB);
+        cartridge.write(0x4000, 0x00);
+        assert_eq!(cartridge.read(0xA000), 0x00);
+        cartridge.write(0x4000, 0x0F);
+        assert_eq!(cartridge.read(0xA000), 0xAB);
+
+        // Registers are write only but for the capture one
+        write_sensor(&mut cartridge, 0x01, 0xFF);
+        assert_eq!(cartridge.read(0xA001), 0x00);
+        assert_eq!(cartridge.read(0xA000), 0x00);
+    }
+
+    #[test]
+    fn poke_reaches_mapped_ram() {
+        let mut cartridge = cartridge();
+
+        cartridge.write(0x0000, 0x0A);
+        cartridge.write(0x4000, 0x03);
+        assert_poke_matches_write(&mut cartridge, 0xA123);
+
+        // With the sensor mapped nothing lands, not even a capture
+        write_sensor(&mut cartridge, 0x00, 0x00);
+        cartridge.poke(0xA000, 0x01);
+        assert_eq!(cartridge.read(0xA000), 0x00);
+        cartridge.write(0x4000, 0x03);
+        assert_eq!(cartridge.read(0xA000), 0x00);
+    }
+
+    #[test]
+    fn capture_writes_dithered_tiles() {
+        let mut cartridge = cartridge();
+        flat_matrix(&mut cartridge);
+        write_sensor(&mut cartridge, 0x02, 0x10);
+        write_sensor(&mut cartridge, 0x03, 0x00);
+
+        // Left half white, right half black
+        let pixels: Vec<u8> = (0..IMAGE_WIDTH * IMAGE_HEIGHT)
+            .map(|i| if i % IMAGE_WIDTH < 64 { 0xFF } else { 0x00 })
+            .collect();
+        cartridge.set_camera_image(IMAGE_WIDTH, IMAGE_HEIGHT, &pixels);
+
+        write_sensor(&mut cartridge, 0x00, 0x01);
+        assert_eq!(cartridge.read(0xA000) & 0x01, 0x01);
+        cartridge.write(0x4000, 0x00);
+        assert_eq!(cartridge.read(0xA100), 0x00);
+
+        capture(&mut cartridge);
+
+        // First tile is colour 0, last tile of the first row colour 3
+        assert_eq!(cartridge.read(0xA100), 0x00);
+        assert_eq!(cartridge.read(0xA101), 0x00);
+        assert_eq!(cartridge.read(0xA1F0), 0xFF);
+        assert_eq!(cartridge.read(0xA1F1), 0xFF);
+
+        // Last tile of the image
+        assert_eq!(cartridge.read(0xA100 + 223 * 16 + 14), 0xFF);
+    }
+
+    #[test]
+    fn invert_and_exposure_work() {
+        let mut cartridge = cartridge();
+        flat_matrix(&mut cartridge);
+        cartridge.set_camera_image(1, 1, &[0x50]);
+
+        // Nominal exposure, between the first two thresholds
+        write_sensor(&mut cartridge, 0x02, 0x10);
+        capture(&mut cartridge);
+        assert_eq!((cartridge.read(0xA100), cartridge.read(0xA101)), (0x00, 0xFF));
+
+        // Double exposure pushes it over 0x80
+        write_sensor(&mut cartridge, 0x02, 0x20);
+        capture(&mut cartridge);
+        assert_eq!((cartridge.read(0xA100), cartridge.read(0xA101)), (0xFF, 0x00));
+
+        write_sensor(&mut cartridge, 0x04, 0x08);
+        capture(&mut cartridge);
+        assert_eq!((cartridge.read(0xA100), cartridge.read(0xA101)), (0x00, 0xFF));
+    }
+
+    #[test]
+    fn edge_enhancement_works() {
+        let mut cartridge = cartridge();
+        flat_matrix(&mut cartridge);
+        write_sensor(&mut cartridge, 0x02, 0x10);
+
+        // A darker column on a light background, flat without edge enhancement
+        let pixels: Vec<u8> = (0..IMAGE_WIDTH * IMAGE_HEIGHT)
+            .map(|i| if i % IMAGE_WIDTH == 3 { 0x90 } else { 0xB0 })
+            .collect();
+        cartridge.set_camera_image(IMAGE_WIDTH, IMAGE_HEIGHT, &pixels);
+
+        capture(&mut cartridge);
+        assert_eq!((cartridge.read(0xA100), cartridge.read(0xA101)), (0xFF, 0x00));
+
+        // Sharpened horizontally the column darkens and its neighbours lighten
+        write_sensor(&mut cartridge, 0x01, 0x20);
+        write_sensor(&mut cartridge, 0x04, 0x20);
+        capture(&mut cartridge);
+        assert_eq!((cartridge.read(0xA100), cartridge.read(0xA101)), (0xC7, 0x10));
+    }
+
+    #[test]
+    fn pgm_works() {
+        let (width, height, pixels) = parse_pgm(b"P5\n# comment\n2 1\n255\n\x00\xFF").unwrap();
+        assert_eq!((width, height, pixels), (2, 1, vec![0x00, 0xFF]));
+
+        let (width, height, pixels) = parse_pgm(b"P2 2 2 15\n0 15\n15 0\n").unwrap();
+        assert_eq!((width, height, pixels), (2, 2, vec![0x00, 0xFF, 0xFF, 0x00]));
+
+        assert!(parse_pgm(b"P6 1 1 255 \x00\x00\x00").is_err());
+        assert!(parse_pgm(b"P5 4 4 255 \x00").is_err());
+        assert!(parse_pgm(b"P5 99999999999 99999999999 255 \x00").is_err());
+        assert!(parse_pgm(b"P2 2 1 15 0 16").is_err());
+
+        let image = resample(2, 1, &[0x00, 0xFF]);
+        assert_eq!((image[0], image[IMAGE_WIDTH - 1]), (0x00, 0xFF));
+    }
+}
@@ -0,0 +1,152 @@
+use crate::cartridge::Mbc;
+
+// https://gbdev.io/pandocs/HuC1.html
+/* [0000, 1FFF] 0x0E maps the infrared port at [A000, BFFF], anything else maps RAM
+ * [2000, 3FFF] ROM Bank Number - 6 bits
+ * [4000, 5FFF] RAM Bank Number - 2 bits
+ */
+
+pub struct HuC1 {
+    infrared_mode: bool,
+    rom_bank: u8,
+    ram_bank: u8,
+    led: bool,
+    light: bool,
+}
+
+impl HuC1 {
+    pub fn new() -> Self {
+        HuC1 {
+            infrared_mode: false,
+            rom_bank: 1,
+            ram_bank: 0,
+            led: false,
+            light: false,
+        }
+    }
+}
+
+impl Mbc for HuC1 {
+    fn rom_bank(&self, address: u16) -> usize {
+        match address {
+            0x0000..=0x3FFF => 0,
+            _ => self.rom_bank as usize,
+        }
+    }
+
+    fn write_register(&mut self, address: u16, value: u8) {
+        match address {
+            0x0000..=0x1FFF => self.infrared_mode = value == 0x0E,
+            0x2000..=0x3FFF => {
+                self.rom_bank = value & 0x3F;
+                if self.rom_bank == 0 { self.rom_bank = 1; }
+            }
+            0x4000..=0x5FFF => self.ram_bank = value & 0x03,
+            _ => (),
+        }
+    }
+
+    fn read_ram(&self, ram: &[[u8; 0x2000]], address: u16) -> u8 {
+        if self.infrared_mode {
+            return 0xC0 | self.light as u8;
+        }
+
+        match self.ram_location(ram, address) {
+            Some((bank, offset)) => ram[bank][offset],
+            None => 0xFF,
+        }
+    }
+
+    fn write_ram(&mut self, ram: &mut [[u8; 0x2000]], address: u16, value: u8) {
+        if self.infrared_mode {
+            self.led = value & 0x01 != 0;
+        } else if let Some((bank, offset)) = self.ram_location(ram, address) {
+            ram[bank][offset] = value;
+        }
+    }
+
+    fn ram_location(&self, ram: &[[u8; 0x2000]], address: u16) -> Option<(usize, usize)> {
+        if self.infrared_mode || ram.is_empty() {
+            return None;
+        }
+
+        Some((self.ram_bank as usize % ram.len(), address as usize & 0x1FFF))
+    }
+
+    fn infrared(&self) -> bool {
+        self.led
+    }
+
+    fn receive_infrared(&mut self, light: bool) {
+        self.light = light;
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::bus::Bus;
+    use crate::cartridge::test::{assert_poke_matches_write, recorder, test_cartridge};
+    use crate::cartridge::Cartridge;
+
+    fn cartridge() -> Cartridge {
+        test_cartridge(64, 4, HuC1::new())
+    }
+
+    #[test]
+    fn banking_works() {
+        let mut cartridge = cartridge();
+
+        cartridge.write(0x2000, 0x3F);
+        assert_eq!(cartridge.read(0x4000), 0x3F);
+        cartridge.write(0x2000, 0x00);
+        assert_eq!(cartridge.read(0x4000), 0x01);
+
+        cartridge.write(0x0000, 0x0A);
+        cartridge.write(0x4000, 0x03);
+        cartridge.write(0xA000, 0xAB);
+        cartridge.write(0x4000, 0x00);
+        assert_eq!(cartridge.read(0xA000), 0x00);
+        cartridge.write(0x4000, 0x03);
+        assert_eq!(cartridge.read(0xA000), 0xAB);
+    }
+
+    #[test]
+    fn infrared_window_works() {
+        let mut cartridge = cartridge();
+        let (events, listener) = recorder();
+        cartridge.on_infrared(listener);
+
+        cartridge.write(0xA000, 0x12);
+        cartridge.write(0x0000, 0x0E);
+        assert_eq!(cartridge.read(0xA000), 0xC0);
+
+        cartridge.receive_infrared(true);
+        assert_eq!(cartridge.read(0xA000), 0xC1);
+
+        cartridge.write(0xA000, 0x01);
+        assert!(cartridge.infrared());
+        cartridge.write(0xA000, 0x00);
+
+        // Back to RAM, which the LED writes left alone
+        cartridge.write(0x0000, 0x0A);
+        assert_eq!(cartridge.read(0xA000), 0x12);
+        assert_eq!(*events.borrow(), vec![true, false]);
+    }
+
+    #[test]
+    fn poke_skips_the_led() {
+        let mut cartridge = cartridge();
+        let (events, listener) = recorder();
+        cartridge.on_infrared(listener);
+
+        cartridge.write(0x4000, 0x06);
+        assert_poke_matches_write(&mut cartridge, 0xB000);
+
+        // The infrared window has no RAM behind it
+        cartridge.write(0x0000, 0x0E);
+        cartridge.poke(0xA000, 0x01);
+        assert!(!cartridge.infrared());
+        assert!(events.borrow().is_empty());
+    }
+}
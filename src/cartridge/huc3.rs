@@ -0,0 +1,366 @@
+use crate::cartridge::{now, Mbc, CLOCK_SPEED};
+
+// https://gbdev.io/pandocs/HuC3.html
+/* [0000, 1FFF] What [A000, BFFF] maps
+ *   0x0A RAM, 0x00 RAM read only
+ *   0x0B RTC command, 0x0C RTC response, 0x0D RTC semaphore
+ *   0x0E Infrared
+ * [2000, 3FFF] ROM Bank Number - 7 bits
+ * [4000, 5FFF] RAM Bank Number
+ */
+
+/* RTC commands, upper nibble of the byte written in mode 0x0B and an argument in the lower one
+ *   0x1 Read the nibble at the address into the response, then increment the address
+ *   0x3 Write the argument at the address, then increment the address
+ *   0x4 Set the low nibble of the address
+ *   0x5 Set the high nibble of the address
+ *   0x6 Extended - 0x0 copies the clock to [00, 05], 0x1 sets the clock from [00, 05],
+ *       0x2 reports ready, 0xE toggles the tone
+ */
+
+/* RTC memory, one nibble per address
+ * [00, 02] Minutes of the day
+ * [03, 05] Days
+ * [10, 12] Alarm minutes of the day
+ * [13, 15] Alarm days
+ * [16] Alarm enable
+ */
+
+const MINUTES_PER_DAY: u16 = 1440;
+// T-cycles in one minute
+const MINUTE: u32 = CLOCK_SPEED * 60;
+// Memory nibbles followed by a u64 unix timestamp
+const RTC_SAVE_SIZE: usize = 0x100 + 8;
+
+pub struct HuC3 {
+    mode: u8,
+    rom_bank: u8,
+    ram_bank: u8,
+    memory: [u8; 0x100],
+    address: u8,
+    command: u8,
+    response: u8,
+    minutes: u16,
+    // 12 bits
+    days: u16,
+    // Cycles into the current minute
+    cycles: u32,
+    tone: bool,
+    led: bool,
+    light: bool,
+}
+
+impl HuC3 {
+    pub fn new() -> Self {
+        HuC3 {
+            mode: 0,
+            rom_bank: 1,
+            ram_bank: 0,
+            memory: [0; 0x100],
+            address: 0,
+            command: 0,
+            response: 0,
+            minutes: 0,
+            days: 0,
+            cycles: 0,
+            tone: false,
+            led: false,
+            light: false,
+        }
+    }
+
+    fn run(&mut self, value: u8) {
+        self.command = value >> 4;
+        let argument = value & 0x0F;
+
+        match self.command {
+            0x1 => {
+                self.response = self.memory[self.address as usize];
+                self.address = self.address.wrapping_add(1);
+            }
+            0x3 => {
+                self.memory[self.address as usize] = argument;
+                self.address = self.address.wrapping_add(1);
+            }
+            0x4 => self.address = (self.address & 0xF0) | argument,
+            0x5 => self.address = (self.address & 0x0F) | argument << 4,
+            0x6 => match argument {
+                0x0 => self.store_clock(),
+                0x1 => self.load_clock(),
+                0x2 => self.response = 0x1,
+                0xE => self.tone = !self.tone,
+                _ => (),
+            },
+            _ => (),
+        }
+    }
+
+    // Where [A000, BFFF] lands while one of the RAM modes is selected
+    fn mapped_ram(&self, ram: &[[u8; 0x2000]], address: u16) -> Option<(usize, usize)> {
+        (!ram.is_empty()).then(|| (self.ram_bank as usize % ram.len(), address as usize & 0x1FFF))
+    }
+
+    fn store_clock(&mut self) {
+        write_nibbles(&mut self.memory[0x00..0x03], self.minutes);
+        write_nibbles(&mut self.memory[0x03..0x06], self.days);
+    }
+
+    fn load_clock(&mut self) {
+        self.minutes = read_nibbles(&self.memory[0x00..0x03]) % MINUTES_PER_DAY;
+        self.days = read_nibbles(&self.memory[0x03..0x06]);
+        self.cycles = 0;
+    }
+
+    fn advance(&mut self, minutes: u64) {
+        let total = self.minutes as u64 + minutes;
+        self.minutes = (total % MINUTES_PER_DAY as u64) as u16;
+        self.days = ((self.days as u64 + total / MINUTES_PER_DAY as u64) & 0xFFF) as u16;
+    }
+
+    fn tick_minute(&mut self) {
+        self.advance(1);
+
+        let alarm = (read_nibbles(&self.memory[0x10..0x13]), read_nibbles(&self.memory[0x13..0x16]));
+        if self.memory[0x16] & 0x01 != 0 && alarm == (self.minutes, self.days) {
+            self.tone = true;
+        }
+    }
+}
+
+// Little endian nibbles
+fn read_nibbles(nibbles: &[u8]) -> u16 {
+    nibbles.iter().rev().fold(0, |value, nibble| value << 4 | (nibble & 0x0F) as u16)
+}
+
+fn write_nibbles(nibbles: &mut [u8], value: u16) {
+    for (i, nibble) in nibbles.iter_mut().enumerate() {
+        *nibble = (value >> (4 * i)) as u8 & 0x0F;
+    }
+}
+
+impl Mbc for HuC3 {
+    fn rom_bank(&self, address: u16) -> usize {
+        match address {
+            0x0000..=0x3FFF => 0,
+            _ => self.rom_bank as usize,
+        }
+    }
+
+    fn write_register(&mut self, address: u16, value: u8) {
+        match address {
+            0x0000..=0x1FFF => self.mode = value & 0x0F,
+            0x2000..=0x3FFF => self.rom_bank = value & 0x7F,
+            0x4000..=0x5FFF => self.ram_bank = value & 0x0F,
+            _ => (),
+        }
+    }
+
+    fn read_ram(&self, ram: &[[u8; 0x2000]], address: u16) -> u8 {
+        match self.mode {
+            0x00 | 0x0A => self.mapped_ram(ram, address).map_or(0xFF, |(bank, offset)| ram[bank][offset]),
+            0x0C => self.command << 4 | self.response,
+            // Commands run straight away, always ready
+            0x0D => 0x01,
+            0x0E => 0xC0 | self.light as u8,
+            _ => 0xFF,
+        }
+    }
+
+    fn write_ram(&mut self, ram: &mut [[u8; 0x2000]], address: u16, value: u8) {
+        match self.mode {
+            0x0A => {
+                if let Some((bank, offset)) = self.ram_location(ram, address) {
+                    ram[bank][offset] = value;
+                }
+            }
+            0x0B => self.run(value),
+            0x0E => self.led = value & 0x01 != 0,
+            _ => (),
+        }
+    }
+
+    // Read only mode 0x00 takes no writes, so only 0x0A has RAM to land on
+    fn ram_location(&self, ram: &[[u8; 0x2000]], address: u16) -> Option<(usize, usize)> {
+        match self.mode {
+            0x0A => self.mapped_ram(ram, address),
+            _ => None,
+        }
+    }
+
+    fn tick(&mut self, cycles: u32) {
+        self.cycles += cycles;
+        while self.cycles >= MINUTE {
+            self.cycles -= MINUTE;
+            self.tick_minute();
+        }
+    }
+
+    fn infrared(&self) -> bool {
+        self.led
+    }
+
+    fn receive_infrared(&mut self, light: bool) {
+        self.light = light;
+    }
+
+    fn tone(&self) -> bool {
+        self.tone
+    }
+
+    fn save(&self, ram: &[[u8; 0x2000]]) -> Vec<u8> {
+        let mut data: Vec<u8> = ram.iter().flatten().copied().collect();
+
+        let mut memory = self.memory;
+        write_nibbles(&mut memory[0x00..0x03], self.minutes);
+        write_nibbles(&mut memory[0x03..0x06], self.days);
+        data.extend_from_slice(&memory);
+        data.extend_from_slice(&now().to_le_bytes());
+
+        data
+    }
+
+    fn load(&mut self, ram: &mut [[u8; 0x2000]], data: &[u8]) {
+        for (byte, value) in ram.iter_mut().flatten().zip(data) {
+            *byte = *value;
+        }
+
+        let rtc = &data[data.len().min(ram.len() * 0x2000)..];
+        if rtc.len() < RTC_SAVE_SIZE {
+            return;
+        }
+
+        for (nibble, value) in self.memory.iter_mut().zip(&rtc[..0x100]) {
+            *nibble = value & 0x0F;
+        }
+        self.load_clock();
+
+        // Keep counting the time the emulator was closed
+        let saved = u64::from_le_bytes(rtc[0x100..0x108].try_into().unwrap());
+        self.advance(now().saturating_sub(saved) / 60);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::bus::Bus;
+    use crate::cartridge::test::{assert_poke_matches_write, test_cartridge};
+    use crate::cartridge::Cartridge;
+
+    fn cartridge() -> Cartridge {
+        let mut cartridge = test_cartridge(64, 4, HuC3::new());
+        cartridge.battery = true;
+        cartridge
+    }
+
+    fn run(cartridge: &mut Cartridge, command: u8) -> u8 {
+        cartridge.write(0x0000, 0x0B);
+        cartridge.write(0xA000, command);
+        cartridge.write(0x0000, 0x0C);
+        cartridge.read(0xA000)
+    }
+
+    fn write_memory(cartridge: &mut Cartridge, address: u8, nibbles: &[u8]) {
+        run(cartridge, 0x40 | (address & 0x0F));
+        run(cartridge, 0x50 | (address >> 4));
+        for nibble in nibbles {
+            run(cartridge, 0x30 | nibble);
+        }
+    }
+
+    fn read_memory(cartridge: &mut Cartridge, address: u8, count: usize) -> Vec<u8> {
+        run(cartridge, 0x40 | (address & 0x0F));
+        run(cartridge, 0x50 | (address >> 4));
+        (0..count).map(|_| run(cartridge, 0x10) & 0x0F).collect()
+    }
+
+    #[test]
+    fn modes_work() {
+        let mut cartridge = cartridge();
+
+        cartridge.write(0x0000, 0x0A);
+        cartridge.write(0x4000, 0x02);
+        cartridge.write(0xA000, 0xAB);
+
+        // Read only
+        cartridge.write(0x0000, 0x00);
+        cartridge.write(0xA000, 0xCD);
+        assert_eq!(cartridge.read(0xA000), 0xAB);
+
+        cartridge.write(0x0000, 0x0D);
+        assert_eq!(cartridge.read(0xA000), 0x01);
+
+        cartridge.write(0x0000, 0x0E);
+        cartridge.receive_infrared(true);
+        assert_eq!(cartridge.read(0xA000), 0xC1);
+        cartridge.write(0xA000, 0x01);
+        assert!(cartridge.infrared());
+    }
+
+    #[test]
+    fn poke_reaches_mapped_ram() {
+        let mut cartridge = cartridge();
+
+        cartridge.write(0x0000, 0x0A);
+        cartridge.write(0x4000, 0x05);
+        assert_poke_matches_write(&mut cartridge, 0xA000);
+
+        // Neither the read only mode nor the command port take it
+        cartridge.write(0x0000, 0x00);
+        cartridge.poke(0xA000, 0x12);
+        assert_eq!(cartridge.read(0xA000), 0xA5);
+
+        cartridge.write(0x0000, 0x0B);
+        cartridge.poke(0xA000, 0x6E);
+        assert!(!cartridge.tone());
+    }
+
+    #[test]
+    fn clock_runs_on_cycles() {
+        let mut cartridge = cartridge();
+
+        // Day 2 at 23:59
+        write_memory(&mut cartridge, 0x00, &[0xF, 0x9, 0x5, 0x2, 0x0, 0x0]);
+        run(&mut cartridge, 0x61);
+
+        cartridge.tick(MINUTE);
+        assert_eq!(run(&mut cartridge, 0x60), 0x60);
+        assert_eq!(read_memory(&mut cartridge, 0x00, 6), vec![0x0, 0x0, 0x0, 0x3, 0x0, 0x0]);
+
+        assert_eq!(run(&mut cartridge, 0x62), 0x61);
+    }
+
+    #[test]
+    fn alarm_plays_tone() {
+        let mut cartridge = cartridge();
+
+        // Alarm at minute 2 of day 0
+        write_memory(&mut cartridge, 0x10, &[0x2, 0x0, 0x0, 0x0, 0x0, 0x0, 0x1]);
+
+        cartridge.tick(MINUTE);
+        assert!(!cartridge.tone());
+        cartridge.tick(MINUTE);
+        assert!(cartridge.tone());
+
+        run(&mut cartridge, 0x6E);
+        assert!(!cartridge.tone());
+    }
+
+    #[test]
+    fn clock_is_saved() {
+        let mut cartridge = cartridge();
+        write_memory(&mut cartridge, 0x03, &[0x5, 0x1, 0x0]);
+        run(&mut cartridge, 0x61);
+        cartridge.tick(MINUTE * 3);
+
+        let save = cartridge.save_data().unwrap();
+        assert_eq!(save.len(), 4 * 0x2000 + RTC_SAVE_SIZE);
+
+        let mut restored = Cartridge::new(64, 4);
+        restored.mbc = Box::new(HuC3::new());
+        restored.load_save_data(&save);
+
+        run(&mut restored, 0x60);
+        assert_eq!(read_memory(&mut restored, 0x00, 6), vec![0x3, 0x0, 0x0, 0x5, 0x1, 0x0]);
+    }
+}
@@ -0,0 +1,211 @@
+use crate::cartridge::Mbc;
+
+// https://gbdev.io/pandocs/MBC1.html
+/* [0000, 1FFF] RAM Enable - 0x0A in the low nibble enables
+ * [2000, 3FFF] ROM Bank Number - 5 bits, 0 behaves as 1
+ * [4000, 5FFF] RAM Bank Number or upper bits of ROM Bank Number - 2 bits
+ * [6000, 7FFF] Banking Mode Select
+ */
+
+const NINTENDO_LOGO: [u8; 48] = [
+    0xCE, 0xED, 0x66, 0x66, 0xCC, 0x0D, 0x00, 0x0B, 0x03, 0x73, 0x00, 0x83, 0x00, 0x0C, 0x00, 0x0D,
+    0x00, 0x08, 0x11, 0x1F, 0x88, 0x89, 0x00, 0x0E, 0xDC, 0xCC, 0x6E, 0xE6, 0xDD, 0xDD, 0xD9, 0x99,
+    0xBB, 0xBB, 0x67, 0x63, 0x6E, 0x0E, 0xEC, 0xCC, 0xDD, 0xDC, 0x99, 0x9F, 0xBB, 0xB9, 0x33, 0x3E,
+];
+
+pub struct Mbc1 {
+    ram_enabled: bool,
+    rom_bank: u8,
+    upper_bank: u8,
+    // Mode 1 also banks [0000, 3FFF] and [A000, BFFF] with the upper bits
+    advanced_mode: bool,
+    // MBC1M skips bit 4 of the rom bank, the upper bits pick one of four 256 KiB games
+    multicart: bool,
+}
+
+impl Mbc1 {
+    pub fn new(multicart: bool) -> Self {
+        Mbc1 {
+            ram_enabled: false,
+            rom_bank: 1,
+            upper_bank: 0,
+            advanced_mode: false,
+            multicart,
+        }
+    }
+
+    /// 1 MiB MBC1 carts with a second boot logo at bank 0x10 are multicarts
+    pub fn is_multicart(bytes: &[u8]) -> bool {
+        let logo = 0x10 * 0x4000 + 0x0104;
+        bytes.len() == 0x10_0000 && bytes[logo..logo + NINTENDO_LOGO.len()] == NINTENDO_LOGO
+    }
+
+    fn upper_bits(&self) -> usize {
+        let shift = if self.multicart { 4 } else { 5 };
+        (self.upper_bank as usize) << shift
+    }
+}
+
+impl Mbc for Mbc1 {
+    fn rom_bank(&self, address: u16) -> usize {
+        match address {
+            0x0000..=0x3FFF if self.advanced_mode => self.upper_bits(),
+            0x0000..=0x3FFF => 0,
+            _ => {
+                let mask = if self.multicart { 0x0F } else { 0x1F };
+                self.upper_bits() | (self.rom_bank & mask) as usize
+            }
+        }
+    }
+
+    fn write_register(&mut self, address: u16, value: u8) {
+        match address {
+            0x0000..=0x1FFF => self.ram_enabled = value & 0x0F == 0x0A,
+            0x2000..=0x3FFF => {
+                // The zero check sees all 5 bits, so 0x20 also becomes 0x01
+                self.rom_bank = value & 0x1F;
+                if self.rom_bank == 0 { self.rom_bank = 1; }
+            }
+            0x4000..=0x5FFF => self.upper_bank = value & 0x03,
+            _ => self.advanced_mode = value & 0x01 != 0,
+        }
+    }
+
+    fn read_ram(&self, ram: &[[u8; 0x2000]], address: u16) -> u8 {
+        match self.ram_location(ram, address) {
+            Some((bank, offset)) if self.ram_enabled => ram[bank][offset],
+            _ => 0xFF,
+        }
+    }
+
+    fn write_ram(&mut self, ram: &mut [[u8; 0x2000]], address: u16, value: u8) {
+        match self.ram_location(ram, address) {
+            Some((bank, offset)) if self.ram_enabled => ram[bank][offset] = value,
+            _ => (),
+        }
+    }
+
+    fn ram_location(&self, ram: &[[u8; 0x2000]], address: u16) -> Option<(usize, usize)> {
+        if ram.is_empty() {
+            return None;
+        }
+
+        let bank = if self.advanced_mode { self.upper_bank as usize } else { 0 };
+        Some((bank % ram.len(), address as usize & 0x1FFF))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::bus::Bus;
+    use crate::cartridge::test::{assert_poke_matches_write, test_cartridge};
+
+    #[test]
+    fn rom_bank_works() {
+        let mut cartridge = test_cartridge(128, 0, Mbc1::new(false));
+
+        assert_eq!(cartridge.read(0x4000), 1);
+
+        cartridge.write(0x2000, 0x00);
+        assert_eq!(cartridge.read(0x4000), 1);
+
+        cartridge.write(0x2000, 0x13);
+        assert_eq!(cartridge.read(0x4000), 0x13);
+
+        cartridge.write(0x4000, 0x02);
+        assert_eq!(cartridge.read(0x4000), 0x53);
+        assert_eq!(cartridge.read(0x0000), 0x00);
+
+        // 0x20 masks to zero and is bumped to 0x21
+        cartridge.write(0x2000, 0x20);
+        cartridge.write(0x4000, 0x01);
+        assert_eq!(cartridge.read(0x4000), 0x21);
+    }
+
+    #[test]
+    fn banking_mode_works() {
+        let mut cartridge = test_cartridge(128, 4, Mbc1::new(false));
+
+        cartridge.write(0x0000, 0x0A);
+        cartridge.write(0xA000, 0xAB);
+        cartridge.write(0x4000, 0x02);
+
+        // Mode 0 keeps bank 0 and RAM bank 0 fixed
+        assert_eq!(cartridge.read(0x0000), 0x00);
+        assert_eq!(cartridge.read(0xA000), 0xAB);
+
+        cartridge.write(0x6000, 0x01);
+        assert_eq!(cartridge.read(0x0000), 0x40);
+        assert_eq!(cartridge.read(0xA000), 0x00);
+
+        cartridge.write(0xA000, 0xCD);
+        cartridge.write(0x6000, 0x00);
+        assert_eq!(cartridge.read(0xA000), 0xAB);
+    }
+
+    #[test]
+    fn ram_enable_works() {
+        let mut cartridge = test_cartridge(4, 1, Mbc1::new(false));
+
+        cartridge.write(0xA000, 0xAB);
+        assert_eq!(cartridge.read(0xA000), 0xFF);
+
+        cartridge.write(0x1FFF, 0x1A);
+        cartridge.write(0xA000, 0xAB);
+        assert_eq!(cartridge.read(0xA000), 0xAB);
+
+        cartridge.write(0x0000, 0x00);
+        assert_eq!(cartridge.read(0xA000), 0xFF);
+    }
+
+    #[test]
+    fn poke_reaches_mapped_ram() {
+        let mut cartridge = test_cartridge(4, 4, Mbc1::new(false));
+        cartridge.write(0x4000, 0x02);
+        cartridge.write(0x6000, 0x01);
+
+        // Disabled RAM still takes the value in the mapped bank
+        cartridge.poke(0xA123, 0xAB);
+        assert_eq!(cartridge.read(0xA123), 0xFF);
+
+        cartridge.write(0x0000, 0x0A);
+        assert_eq!(cartridge.read(0xA123), 0xAB);
+        assert_poke_matches_write(&mut cartridge, 0xA123);
+
+        cartridge.write(0x6000, 0x00);
+        assert_eq!(cartridge.read(0xA123), 0x00);
+        assert_poke_matches_write(&mut cartridge, 0xBFFF);
+    }
+
+    #[test]
+    fn small_rom_masks_bank() {
+        let mut cartridge = test_cartridge(8, 0, Mbc1::new(false));
+
+        cartridge.write(0x2000, 0x1D);
+        assert_eq!(cartridge.read(0x4000), 0x05);
+
+        cartridge.write(0x4000, 0x03);
+        cartridge.write(0x6000, 0x01);
+        assert_eq!(cartridge.read(0x0000), 0x00);
+    }
+
+    #[test]
+    fn multicart_works() {
+        let mut cartridge = test_cartridge(64, 0, Mbc1::new(true));
+
+        cartridge.write(0x2000, 0x12);
+        assert_eq!(cartridge.read(0x4000), 0x02);
+
+        cartridge.write(0x4000, 0x03);
+        assert_eq!(cartridge.read(0x4000), 0x32);
+
+        cartridge.write(0x6000, 0x01);
+        assert_eq!(cartridge.read(0x0000), 0x30);
+
+        let mut bytes = vec![0; 0x10_0000];
+        assert!(!Mbc1::is_multicart(&bytes));
+        bytes[0x4_0104..0x4_0134].copy_from_slice(&NINTENDO_LOGO);
+        assert!(Mbc1::is_multicart(&bytes));
+    }
+}
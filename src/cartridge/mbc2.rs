@@ -0,0 +1,142 @@
+use crate::cartridge::Mbc;
+
+// https://gbdev.io/pandocs/MBC2.html
+/* [0000, 3FFF] RAM Enable when address bit 8 is clear, ROM Bank Number when set
+ * [A000, A1FF] 512 x 4 bits of built-in RAM
+ * [A200, BFFF] Echoes of [A000, A1FF]
+ */
+
+const RAM_SIZE: usize = 0x200;
+
+pub struct Mbc2 {
+    ram_enabled: bool,
+    rom_bank: u8,
+}
+
+impl Mbc2 {
+    pub fn new() -> Self {
+        Mbc2 {
+            ram_enabled: false,
+            rom_bank: 1,
+        }
+    }
+}
+
+impl Mbc for Mbc2 {
+    fn rom_bank(&self, address: u16) -> usize {
+        match address {
+            0x0000..=0x3FFF => 0,
+            _ => self.rom_bank as usize,
+        }
+    }
+
+    fn write_register(&mut self, address: u16, value: u8) {
+        match address {
+            0x0000..=0x3FFF if address & 0x0100 == 0 => self.ram_enabled = value & 0x0F == 0x0A,
+            0x0000..=0x3FFF => {
+                self.rom_bank = value & 0x0F;
+                if self.rom_bank == 0 { self.rom_bank = 1; }
+            }
+            _ => (),
+        }
+    }
+
+    fn read_ram(&self, ram: &[[u8; 0x2000]], address: u16) -> u8 {
+        match self.ram_location(ram, address) {
+            // Only 4 data lines, the upper nibble floats high
+            Some((bank, offset)) if self.ram_enabled => ram[bank][offset] | 0xF0,
+            _ => 0xFF,
+        }
+    }
+
+    fn write_ram(&mut self, ram: &mut [[u8; 0x2000]], address: u16, value: u8) {
+        match self.ram_location(ram, address) {
+            Some((bank, offset)) if self.ram_enabled => ram[bank][offset] = value & 0x0F,
+            _ => (),
+        }
+    }
+
+    fn ram_location(&self, ram: &[[u8; 0x2000]], address: u16) -> Option<(usize, usize)> {
+        (!ram.is_empty()).then_some((0, address as usize & (RAM_SIZE - 1)))
+    }
+
+    fn save(&self, ram: &[[u8; 0x2000]]) -> Vec<u8> {
+        ram[0][..RAM_SIZE].to_vec()
+    }
+
+    fn load(&mut self, ram: &mut [[u8; 0x2000]], data: &[u8]) {
+        for (byte, value) in ram[0][..RAM_SIZE].iter_mut().zip(data) {
+            *byte = value & 0x0F;
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::bus::Bus;
+    use crate::cartridge::test::{assert_poke_matches_write, test_cartridge};
+    use crate::cartridge::Cartridge;
+
+    fn cartridge() -> Cartridge {
+        let mut cartridge = test_cartridge(16, 1, Mbc2::new());
+        cartridge.battery = true;
+        cartridge
+    }
+
+    #[test]
+    fn address_bit_8_selects_register() {
+        let mut cartridge = cartridge();
+
+        // Bit 8 clear, RAM enable
+        cartridge.write(0x0000, 0x0A);
+        assert_eq!(cartridge.read(0x4000), 1);
+
+        // Bit 8 set, ROM bank
+        cartridge.write(0x2100, 0x0A);
+        assert_eq!(cartridge.read(0x4000), 0x0A);
+
+        cartridge.write(0x3F00, 0x00);
+        assert_eq!(cartridge.read(0x4000), 1);
+
+        cartridge.write(0x0100, 0x1F);
+        assert_eq!(cartridge.read(0x4000), 0x0F);
+        assert_eq!(cartridge.read(0x0000), 0x00);
+
+        cartridge.write(0xA000, 0x05);
+        assert_eq!(cartridge.read(0xA000), 0xF5);
+
+        cartridge.write(0x3E00, 0x00);
+        assert_eq!(cartridge.read(0xA000), 0xFF);
+    }
+
+    #[test]
+    fn ram_is_echoed_and_saved() {
+        let (mut cartridge, mut restored) = (cartridge(), cartridge());
+        cartridge.write(0x0000, 0x0A);
+
+        cartridge.write(0xA123, 0xAB);
+        assert_eq!(cartridge.read(0xA123), 0xFB);
+        assert_eq!(cartridge.read(0xA323), 0xFB);
+        assert_eq!(cartridge.read(0xBF23), 0xFB);
+
+        let save = cartridge.save_data().unwrap();
+        assert_eq!(save.len(), 0x200);
+        assert_eq!(save[0x123], 0x0B);
+
+        restored.load_save_data(&save);
+        restored.write(0x0000, 0x0A);
+        assert_eq!(restored.read(0xB123), 0xFB);
+    }
+
+    #[test]
+    fn poke_reaches_echoed_ram() {
+        let mut cartridge = cartridge();
+        cartridge.poke(0xA123, 0x0B);
+
+        cartridge.write(0x0000, 0x0A);
+        assert_eq!(cartridge.read(0xB323), 0xFB);
+        assert_poke_matches_write(&mut cartridge, 0xA123);
+        assert_poke_matches_write(&mut cartridge, 0xBF23);
+    }
+}
@@ -0,0 +1,464 @@
+use crate::cartridge::{now, Mbc, CLOCK_SPEED};
+
+// https://gbdev.io/pandocs/MBC3.html
+/* [0000, 1FFF] RAM and Timer Enable - 0x0A in the low nibble enables
+ * [2000, 3FFF] ROM Bank Number - 7 bits, 0 behaves as 1
+ * [4000, 5FFF] RAM Bank Number 0x00~0x03 or RTC Register Select 0x08~0x0C
+ * [6000, 7FFF] Latch Clock Data - 0x00 then 0x01 copies the clock into the registers
+ */
+
+// Live and latched registers as 5 little endian u32 each, then a u64 unix timestamp
+const RTC_SAVE_SIZE: usize = 48;
+
+#[derive(Clone, Copy, Default, PartialEq, Debug)]
+struct Clock {
+    seconds: u8,
+    minutes: u8,
+    hours: u8,
+    // 9 bits
+    days: u16,
+    halt: bool,
+    // Set when days overflows, stays until written
+    carry: bool,
+}
+
+impl Clock {
+    fn read(&self, register: u8) -> u8 {
+        match register {
+            0x08 => self.seconds,
+            0x09 => self.minutes,
+            0x0A => self.hours,
+            0x0B => self.days as u8,
+            _ => (self.days >> 8) as u8 | (self.halt as u8) << 6 | (self.carry as u8) << 7,
+        }
+    }
+
+    fn write(&mut self, register: u8, value: u8) {
+        match register {
+            0x08 => self.seconds = value & 0x3F,
+            0x09 => self.minutes = value & 0x3F,
+            0x0A => self.hours = value & 0x1F,
+            0x0B => self.days = (self.days & 0x100) | value as u16,
+            _ => {
+                self.days = (self.days & 0xFF) | ((value as u16 & 0x01) << 8);
+                self.halt = value & 0x40 != 0;
+                self.carry = value & 0x80 != 0;
+            }
+        }
+    }
+
+    // Out of range values count up to the register width and wrap without a carry
+    fn tick_second(&mut self) {
+        self.seconds += 1;
+        match self.seconds {
+            60 => { self.seconds = 0; self.tick_minute(); }
+            64 => self.seconds = 0,
+            _ => (),
+        }
+    }
+
+    fn tick_minute(&mut self) {
+        self.minutes += 1;
+        match self.minutes {
+            60 => { self.minutes = 0; self.tick_hour(); }
+            64 => self.minutes = 0,
+            _ => (),
+        }
+    }
+
+    fn tick_hour(&mut self) {
+        self.hours += 1;
+        match self.hours {
+            24 => { self.hours = 0; self.tick_day(); }
+            32 => self.hours = 0,
+            _ => (),
+        }
+    }
+
+    fn tick_day(&mut self) {
+        self.days += 1;
+        if self.days == 512 {
+            self.days = 0;
+            self.carry = true;
+        }
+    }
+
+    /// Skip ahead, whole minutes, hours and days at a time once the lower registers are zero
+    fn advance(&mut self, mut seconds: u64) {
+        while seconds > 0 {
+            let aligned = self.seconds == 0;
+            if aligned && self.minutes == 0 && self.hours == 0 && seconds >= 86_400 {
+                self.tick_day();
+                seconds -= 86_400;
+            } else if aligned && self.minutes == 0 && seconds >= 3_600 {
+                self.tick_hour();
+                seconds -= 3_600;
+            } else if aligned && seconds >= 60 {
+                self.tick_minute();
+                seconds -= 60;
+            } else {
+                self.tick_second();
+                seconds -= 1;
+            }
+        }
+    }
+
+    fn save(&self, data: &mut Vec<u8>) {
+        for register in 0x08..=0x0C {
+            data.extend_from_slice(&(self.read(register) as u32).to_le_bytes());
+        }
+    }
+
+    fn load(&mut self, data: &[u8]) {
+        for (register, bytes) in (0x08..=0x0C).zip(data.chunks_exact(4)) {
+            self.write(register, bytes[0]);
+        }
+    }
+}
+
+pub struct Mbc3 {
+    ram_enabled: bool,
+    rom_bank: u8,
+    // RAM bank or RTC register
+    select: u8,
+    timer: bool,
+    clock: Clock,
+    latched: Clock,
+    latch_armed: bool,
+    // Cycles into the current second
+    cycles: u32,
+}
+
+impl Mbc3 {
+    pub fn new(timer: bool) -> Self {
+        Mbc3 {
+            ram_enabled: false,
+            rom_bank: 1,
+            select: 0,
+            timer,
+            clock: Clock::default(),
+            latched: Clock::default(),
+            latch_armed: false,
+            cycles: 0,
+        }
+    }
+}
+
+impl Mbc for Mbc3 {
+    fn rom_bank(&self, address: u16) -> usize {
+        match address {
+            0x0000..=0x3FFF => 0,
+            _ => self.rom_bank as usize,
+        }
+    }
+
+    fn write_register(&mut self, address: u16, value: u8) {
+        match address {
+            0x0000..=0x1FFF => self.ram_enabled = value & 0x0F == 0x0A,
+            0x2000..=0x3FFF => {
+                self.rom_bank = value & 0x7F;
+                if self.rom_bank == 0 { self.rom_bank = 1; }
+            }
+            0x4000..=0x5FFF => self.select = value,
+            _ => {
+                if self.latch_armed && value == 0x01 {
+                    self.latched = self.clock;
+                }
+                self.latch_armed = value == 0x00;
+            }
+        }
+    }
+
+    fn read_ram(&self, ram: &[[u8; 0x2000]], address: u16) -> u8 {
+        if !self.ram_enabled {
+            return 0xFF;
+        }
+
+        if let Some((bank, offset)) = self.ram_location(ram, address) {
+            return ram[bank][offset];
+        }
+
+        match self.select {
+            0x08..=0x0C if self.timer => self.latched.read(self.select),
+            _ => 0xFF,
+        }
+    }
+
+    fn write_ram(&mut self, ram: &mut [[u8; 0x2000]], address: u16, value: u8) {
+        if !self.ram_enabled {
+            return;
+        }
+
+        if let Some((bank, offset)) = self.ram_location(ram, address) {
+            ram[bank][offset] = value;
+            return;
+        }
+
+        match self.select {
+            0x08..=0x0C if self.timer => {
+                // Writing the seconds also restarts the current second
+                if self.select == 0x08 {
+                    self.cycles = 0;
+                }
+                // Only the live counters, the latched copy waits for the next latch
+                self.clock.write(self.select, value);
+            }
+            _ => (),
+        }
+    }
+
+    fn ram_location(&self, ram: &[[u8; 0x2000]], address: u16) -> Option<(usize, usize)> {
+        match self.select {
+            0x00..=0x03 if !ram.is_empty() => Some((self.select as usize % ram.len(), address as usize & 0x1FFF)),
+            _ => None,
+        }
+    }
+
+    fn tick(&mut self, cycles: u32) {
+        if !self.timer || self.clock.halt {
+            return;
+        }
+
+        self.cycles += cycles;
+        while self.cycles >= CLOCK_SPEED {
+            self.cycles -= CLOCK_SPEED;
+            self.clock.tick_second();
+        }
+    }
+
+    fn save(&self, ram: &[[u8; 0x2000]]) -> Vec<u8> {
+        let mut data: Vec<u8> = ram.iter().flatten().copied().collect();
+
+        if self.timer {
+            self.clock.save(&mut data);
+            self.latched.save(&mut data);
+            data.extend_from_slice(&now().to_le_bytes());
+        }
+
+        data
+    }
+
+    fn load(&mut self, ram: &mut [[u8; 0x2000]], data: &[u8]) {
+        for (byte, value) in ram.iter_mut().flatten().zip(data) {
+            *byte = *value;
+        }
+
+        let rtc = &data[data.len().min(ram.len() * 0x2000)..];
+        if !self.timer || rtc.len() < RTC_SAVE_SIZE {
+            return;
+        }
+
+        self.clock.load(&rtc[0..20]);
+        self.latched.load(&rtc[20..40]);
+
+        // Keep counting the time the emulator was closed
+        let saved = u64::from_le_bytes(rtc[40..48].try_into().unwrap());
+        if !self.clock.halt {
+            self.clock.advance(now().saturating_sub(saved));
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::bus::Bus;
+    use crate::cartridge::test::{assert_poke_matches_write, test_cartridge};
+    use crate::cartridge::Cartridge;
+
+    fn cartridge() -> Cartridge {
+        let mut cartridge = test_cartridge(128, 4, Mbc3::new(true));
+        cartridge.battery = true;
+        cartridge.write(0x0000, 0x0A);
+        cartridge
+    }
+
+    fn latch(cartridge: &mut Cartridge) {
+        cartridge.write(0x6000, 0x00);
+        cartridge.write(0x6000, 0x01);
+    }
+
+    fn read_rtc(cartridge: &mut Cartridge, register: u8) -> u8 {
+        cartridge.write(0x4000, register);
+        cartridge.read(0xA000)
+    }
+
+    fn write_rtc(cartridge: &mut Cartridge, register: u8, value: u8) {
+        cartridge.write(0x4000, register);
+        cartridge.write(0xA000, value);
+    }
+
+    #[test]
+    fn banking_works() {
+        let mut cartridge = cartridge();
+
+        cartridge.write(0x2000, 0x00);
+        assert_eq!(cartridge.read(0x4000), 1);
+
+        cartridge.write(0x2000, 0xFF);
+        assert_eq!(cartridge.read(0x4000), 0x7F);
+
+        for bank in 0..4 {
+            cartridge.write(0x4000, bank);
+            cartridge.write(0xA000, bank + 0x10);
+        }
+        for bank in 0..4 {
+            cartridge.write(0x4000, bank);
+            assert_eq!(cartridge.read(0xA000), bank + 0x10);
+        }
+
+        cartridge.write(0x0000, 0x00);
+        assert_eq!(cartridge.read(0xA000), 0xFF);
+    }
+
+    #[test]
+    fn poke_skips_the_clock() {
+        let mut cartridge = cartridge();
+
+        cartridge.write(0x4000, 0x06);
+        assert_poke_matches_write(&mut cartridge, 0xB000);
+
+        // Nothing to land on while a clock register is mapped
+        write_rtc(&mut cartridge, 0x08, 0x12);
+        latch(&mut cartridge);
+        cartridge.poke(0xA000, 0x34);
+        latch(&mut cartridge);
+        assert_eq!(read_rtc(&mut cartridge, 0x08), 0x12);
+    }
+
+    #[test]
+    fn clock_runs_on_cycles_and_latches() {
+        let mut cartridge = cartridge();
+
+        cartridge.tick(CLOCK_SPEED * 61);
+        assert_eq!(read_rtc(&mut cartridge, 0x08), 0);
+
+        latch(&mut cartridge);
+        assert_eq!(read_rtc(&mut cartridge, 0x08), 1);
+        assert_eq!(read_rtc(&mut cartridge, 0x09), 1);
+
+        // Only 0x00 followed by 0x01 latches
+        cartridge.tick(CLOCK_SPEED);
+        cartridge.write(0x6000, 0x01);
+        assert_eq!(read_rtc(&mut cartridge, 0x08), 1);
+
+        cartridge.write(0x6000, 0x00);
+        cartridge.write(0x6000, 0x01);
+        assert_eq!(read_rtc(&mut cartridge, 0x08), 2);
+    }
+
+    #[test]
+    fn writes_wait_for_the_latch() {
+        let mut cartridge = cartridge();
+
+        cartridge.tick(CLOCK_SPEED * 5);
+        latch(&mut cartridge);
+
+        write_rtc(&mut cartridge, 0x08, 30);
+        write_rtc(&mut cartridge, 0x0A, 12);
+        assert_eq!(read_rtc(&mut cartridge, 0x08), 5);
+        assert_eq!(read_rtc(&mut cartridge, 0x0A), 0);
+
+        latch(&mut cartridge);
+        assert_eq!(read_rtc(&mut cartridge, 0x08), 30);
+        assert_eq!(read_rtc(&mut cartridge, 0x0A), 12);
+    }
+
+    #[test]
+    fn halt_stops_clock() {
+        let mut cartridge = cartridge();
+
+        write_rtc(&mut cartridge, 0x0C, 0x40);
+        cartridge.tick(CLOCK_SPEED * 10);
+        latch(&mut cartridge);
+        assert_eq!(read_rtc(&mut cartridge, 0x08), 0);
+
+        write_rtc(&mut cartridge, 0x0C, 0x00);
+        cartridge.tick(CLOCK_SPEED * 10);
+        latch(&mut cartridge);
+        assert_eq!(read_rtc(&mut cartridge, 0x08), 10);
+    }
+
+    #[test]
+    fn day_counter_carries() {
+        let mut cartridge = cartridge();
+
+        write_rtc(&mut cartridge, 0x08, 59);
+        write_rtc(&mut cartridge, 0x09, 59);
+        write_rtc(&mut cartridge, 0x0A, 23);
+        write_rtc(&mut cartridge, 0x0B, 0xFF);
+        write_rtc(&mut cartridge, 0x0C, 0x00);
+
+        cartridge.tick(CLOCK_SPEED);
+        latch(&mut cartridge);
+        assert_eq!(read_rtc(&mut cartridge, 0x0B), 0x00);
+        assert_eq!(read_rtc(&mut cartridge, 0x0C), 0x01);
+
+        write_rtc(&mut cartridge, 0x08, 59);
+        write_rtc(&mut cartridge, 0x09, 59);
+        write_rtc(&mut cartridge, 0x0A, 23);
+        write_rtc(&mut cartridge, 0x0B, 0xFF);
+        write_rtc(&mut cartridge, 0x0C, 0x01);
+
+        cartridge.tick(CLOCK_SPEED);
+        latch(&mut cartridge);
+        assert_eq!(read_rtc(&mut cartridge, 0x0B), 0x00);
+        assert_eq!(read_rtc(&mut cartridge, 0x0C), 0x80);
+    }
+
+    #[test]
+    fn invalid_values_wrap_without_carry() {
+        let mut cartridge = cartridge();
+
+        write_rtc(&mut cartridge, 0x08, 0xFF);
+        write_rtc(&mut cartridge, 0x0A, 0x1F);
+        latch(&mut cartridge);
+        assert_eq!(read_rtc(&mut cartridge, 0x08), 0x3F);
+
+        cartridge.tick(CLOCK_SPEED);
+        latch(&mut cartridge);
+        assert_eq!(read_rtc(&mut cartridge, 0x08), 0);
+        assert_eq!(read_rtc(&mut cartridge, 0x09), 0);
+
+        let mut clock = Clock { minutes: 59, seconds: 59, hours: 31, ..Clock::default() };
+        clock.tick_second();
+        assert_eq!((clock.hours, clock.days), (0, 0));
+    }
+
+    #[test]
+    fn advance_matches_ticking() {
+        let mut fast = Clock { seconds: 62, minutes: 12, hours: 25, days: 100, ..Clock::default() };
+        let mut slow = fast;
+
+        let seconds = 3 * 86_400 + 5 * 3_600 + 7 * 60 + 11;
+        fast.advance(seconds);
+        for _ in 0..seconds {
+            slow.tick_second();
+        }
+
+        assert_eq!(fast, slow);
+    }
+
+    #[test]
+    fn clock_is_saved() {
+        let mut cartridge = cartridge();
+        write_rtc(&mut cartridge, 0x09, 42);
+        write_rtc(&mut cartridge, 0x0C, 0x40);
+        latch(&mut cartridge);
+        cartridge.write(0x4000, 0x02);
+        cartridge.write(0xA000, 0xAB);
+
+        let save = cartridge.save_data().unwrap();
+        assert_eq!(save.len(), 4 * 0x2000 + RTC_SAVE_SIZE);
+
+        let mut restored = Cartridge::new(128, 4);
+        restored.mbc = Box::new(Mbc3::new(true));
+        restored.load_save_data(&save);
+        restored.write(0x0000, 0x0A);
+
+        assert_eq!(read_rtc(&mut restored, 0x09), 42);
+        assert_eq!(read_rtc(&mut restored, 0x0C), 0x40);
+        restored.write(0x4000, 0x02);
+        assert_eq!(restored.read(0xA000), 0xAB);
+    }
+}
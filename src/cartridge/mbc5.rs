@@ -0,0 +1,152 @@
+use crate::cartridge::Mbc;
+
+// https://gbdev.io/pandocs/MBC5.html
+/* [0000, 1FFF] RAM Enable - 0x0A enables
+ * [2000, 2FFF] Low 8 bits of ROM Bank Number, 0 selects bank 0
+ * [3000, 3FFF] Bit 8 of ROM Bank Number
+ * [4000, 5FFF] RAM Bank Number - 4 bits, bit 3 drives the motor on rumble carts
+ */
+
+pub struct Mbc5 {
+    ram_enabled: bool,
+    // 9 bits
+    rom_bank: u16,
+    ram_bank: u8,
+    has_rumble: bool,
+    rumble: bool,
+}
+
+impl Mbc5 {
+    pub fn new(has_rumble: bool) -> Self {
+        Mbc5 {
+            ram_enabled: false,
+            rom_bank: 1,
+            ram_bank: 0,
+            has_rumble,
+            rumble: false,
+        }
+    }
+}
+
+impl Mbc for Mbc5 {
+    fn rom_bank(&self, address: u16) -> usize {
+        match address {
+            0x0000..=0x3FFF => 0,
+            _ => self.rom_bank as usize,
+        }
+    }
+
+    fn write_register(&mut self, address: u16, value: u8) {
+        match address {
+            // Unlike MBC1 all 8 bits are checked
+            0x0000..=0x1FFF => self.ram_enabled = value == 0x0A,
+            0x2000..=0x2FFF => self.rom_bank = (self.rom_bank & 0x100) | value as u16,
+            0x3000..=0x3FFF => self.rom_bank = (self.rom_bank & 0xFF) | ((value as u16 & 0x01) << 8),
+            0x4000..=0x5FFF if self.has_rumble => {
+                self.rumble = value & 0x08 != 0;
+                self.ram_bank = value & 0x07;
+            }
+            0x4000..=0x5FFF => self.ram_bank = value & 0x0F,
+            _ => (),
+        }
+    }
+
+    fn read_ram(&self, ram: &[[u8; 0x2000]], address: u16) -> u8 {
+        match self.ram_location(ram, address) {
+            Some((bank, offset)) if self.ram_enabled => ram[bank][offset],
+            _ => 0xFF,
+        }
+    }
+
+    fn write_ram(&mut self, ram: &mut [[u8; 0x2000]], address: u16, value: u8) {
+        match self.ram_location(ram, address) {
+            Some((bank, offset)) if self.ram_enabled => ram[bank][offset] = value,
+            _ => (),
+        }
+    }
+
+    fn ram_location(&self, ram: &[[u8; 0x2000]], address: u16) -> Option<(usize, usize)> {
+        (!ram.is_empty()).then(|| (self.ram_bank as usize % ram.len(), address as usize & 0x1FFF))
+    }
+
+    fn rumble(&self) -> bool {
+        self.rumble
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::bus::Bus;
+    use crate::cartridge::test::{assert_poke_matches_write, recorder, test_cartridge};
+    use crate::cartridge::Cartridge;
+
+    fn cartridge(rumble: bool) -> Cartridge {
+        let mut cartridge = test_cartridge(512, 16, Mbc5::new(rumble));
+        cartridge.write(0x0000, 0x0A);
+        cartridge
+    }
+
+    #[test]
+    fn rom_bank_works() {
+        let mut cartridge = cartridge(false);
+        assert_eq!(cartridge.read(0x4000), 1);
+
+        cartridge.write(0x2000, 0x00);
+        assert_eq!(cartridge.read(0x4000), 0);
+
+        cartridge.write(0x2000, 0x23);
+        cartridge.write(0x3000, 0x01);
+        assert_eq!((cartridge.read(0x4000), cartridge.read(0x4001)), (0x23, 0x01));
+
+        cartridge.write(0x3000, 0x00);
+        assert_eq!((cartridge.read(0x4000), cartridge.read(0x4001)), (0x23, 0x00));
+        assert_eq!(cartridge.read(0x0000), 0);
+    }
+
+    #[test]
+    fn ram_banks_work() {
+        let mut cartridge = cartridge(false);
+
+        for bank in 0..16 {
+            cartridge.write(0x4000, bank);
+            cartridge.write(0xB000, bank);
+        }
+        for bank in 0..16 {
+            cartridge.write(0x4000, bank);
+            assert_eq!(cartridge.read(0xB000), bank);
+        }
+
+        cartridge.write(0x0000, 0x1A);
+        assert_eq!(cartridge.read(0xB000), 0xFF);
+    }
+
+    #[test]
+    fn poke_reaches_mapped_ram() {
+        let mut cartridge = cartridge(true);
+        cartridge.write(0x4000, 0x03);
+
+        let (events, listener) = recorder();
+        cartridge.on_rumble(listener);
+        assert_poke_matches_write(&mut cartridge, 0xA000);
+        assert_poke_matches_write(&mut cartridge, 0xBFFF);
+        assert!(events.borrow().is_empty());
+    }
+
+    #[test]
+    fn rumble_is_reported() {
+        let mut cartridge = cartridge(true);
+        let (events, listener) = recorder();
+        cartridge.on_rumble(listener);
+
+        cartridge.write(0x4000, 0x0A);
+        assert!(cartridge.rumble());
+        cartridge.write(0xA000, 0x12);
+
+        cartridge.write(0x4000, 0x0A);
+        cartridge.write(0x4000, 0x02);
+        assert_eq!(cartridge.read(0xA000), 0x12);
+
+        assert_eq!(*events.borrow(), vec![true, false]);
+    }
+}
@@ -0,0 +1,463 @@
+use crate::cartridge::Mbc;
+
+// https://gbdev.io/pandocs/MBC7.html
+/* [0000, 1FFF] RAM Enable 1 - 0x0A enables
+ * [2000, 3FFF] ROM Bank Number
+ * [4000, 5FFF] RAM Enable 2 - 0x40 enables
+ * [A000, AFFF] Registers, selected by address bits 4~7
+ *   Ax0x Write 0x55 to erase the latched accelerometer values
+ *   Ax1x Write 0xAA to latch the accelerometer
+ *   Ax2x, Ax3x Accelerometer X low and high byte
+ *   Ax4x, Ax5x Accelerometer Y low and high byte
+ *   Ax8x EEPROM - bit 7 CS, bit 6 CLK, bit 1 DI, bit 0 DO
+ */
+
+// Words in the 93LC56 with 16 bit organization
+const EEPROM_WORDS: usize = 128;
+// Latched value at rest and how much 1 g moves it
+const ACCELEROMETER_CENTER: f32 = 0x81D0 as f32;
+const ACCELEROMETER_G: f32 = 0x70 as f32;
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+enum Phase {
+    // Waiting for a 1 start bit
+    Idle,
+    // 2 bit opcode and 8 bit address
+    Command,
+    Read(u8),
+    Write(u8),
+    WriteAll,
+    // Finished, waiting for CS to drop
+    Done,
+}
+
+/// 93LC56 serial EEPROM, clocked one bit at a time through Ax8x
+struct Eeprom {
+    chip_select: bool,
+    clock: bool,
+    data_out: bool,
+    write_enabled: bool,
+    phase: Phase,
+    shift: u16,
+    count: u8,
+}
+
+impl Eeprom {
+    fn new() -> Self {
+        Eeprom {
+            chip_select: false,
+            clock: false,
+            data_out: true,
+            write_enabled: false,
+            phase: Phase::Idle,
+            shift: 0,
+            count: 0,
+        }
+    }
+
+    fn read(&self) -> u8 {
+        (self.chip_select as u8) << 7 | (self.clock as u8) << 6 | self.data_out as u8
+    }
+
+    fn write(&mut self, words: &mut [u8], value: u8) {
+        let chip_select = value & 0x80 != 0;
+        let clock = value & 0x40 != 0;
+        let data_in = value & 0x02 != 0;
+
+        if !chip_select {
+            self.phase = Phase::Idle;
+        } else if clock && !self.clock && self.chip_select {
+            self.rising_edge(words, data_in);
+        }
+
+        self.chip_select = chip_select;
+        self.clock = clock;
+    }
+
+    fn rising_edge(&mut self, words: &mut [u8], data_in: bool) {
+        match self.phase {
+            Phase::Idle => {
+                if data_in {
+                    self.phase = Phase::Command;
+                    self.shift = 0;
+                    self.count = 0;
+                }
+            }
+            Phase::Command => {
+                if self.shift_in(data_in, 10) {
+                    self.command(words);
+                }
+            }
+            Phase::Read(address) => {
+                // Data goes out MSB first, then the next word follows
+                self.data_out = self.shift & 0x8000 != 0;
+                self.shift <<= 1;
+                self.count += 1;
+                if self.count == 16 {
+                    let next = address.wrapping_add(1);
+                    self.shift = read_word(words, next);
+                    self.count = 0;
+                    self.phase = Phase::Read(next);
+                }
+            }
+            Phase::Write(address) => {
+                if self.shift_in(data_in, 16) {
+                    if self.write_enabled {
+                        write_word(words, address, self.shift);
+                    }
+                    self.finish();
+                }
+            }
+            Phase::WriteAll => {
+                if self.shift_in(data_in, 16) {
+                    if self.write_enabled {
+                        for address in 0..EEPROM_WORDS as u8 {
+                            write_word(words, address, self.shift);
+                        }
+                    }
+                    self.finish();
+                }
+            }
+            Phase::Done => (),
+        }
+    }
+
+    // True once bits have been collected
+    fn shift_in(&mut self, data_in: bool, bits: u8) -> bool {
+        self.shift = self.shift << 1 | data_in as u16;
+        self.count += 1;
+        self.count == bits
+    }
+
+    fn command(&mut self, words: &mut [u8]) {
+        let opcode = self.shift >> 8;
+        let address = self.shift as u8;
+        self.shift = 0;
+        self.count = 0;
+
+        match (opcode, address >> 6) {
+            // READ, a dummy 0 comes out before the data
+            (0b10, _) => {
+                self.data_out = false;
+                self.shift = read_word(words, address);
+                self.phase = Phase::Read(address);
+            }
+            // WRITE
+            (0b01, _) => self.phase = Phase::Write(address),
+            // ERASE
+            (0b11, _) => {
+                if self.write_enabled {
+                    write_word(words, address, 0xFFFF);
+                }
+                self.finish();
+            }
+            // EWDS
+            (_, 0b00) => {
+                self.write_enabled = false;
+                self.phase = Phase::Done;
+            }
+            // WRAL
+            (_, 0b01) => self.phase = Phase::WriteAll,
+            // ERAL
+            (_, 0b10) => {
+                if self.write_enabled {
+                    for address in 0..EEPROM_WORDS as u8 {
+                        write_word(words, address, 0xFFFF);
+                    }
+                }
+                self.finish();
+            }
+            // EWEN
+            _ => {
+                self.write_enabled = true;
+                self.phase = Phase::Done;
+            }
+        }
+    }
+
+    // Busy writing is instant, DO reports ready straight away
+    fn finish(&mut self) {
+        self.data_out = true;
+        self.phase = Phase::Done;
+    }
+}
+
+fn read_word(words: &[u8], address: u8) -> u16 {
+    let index = (address as usize % EEPROM_WORDS) * 2;
+    u16::from_le_bytes([words[index], words[index + 1]])
+}
+
+fn write_word(words: &mut [u8], address: u8, value: u16) {
+    let index = (address as usize % EEPROM_WORDS) * 2;
+    words[index..index + 2].copy_from_slice(&value.to_le_bytes());
+}
+
+pub struct Mbc7 {
+    ram_enabled: bool,
+    registers_enabled: bool,
+    rom_bank: u8,
+    // Host tilt in g, positive x to the right and positive y down
+    tilt: (f32, f32),
+    latched: (u16, u16),
+    // Set by the 0x55 erase, needed before the 0xAA latch
+    erased: bool,
+    eeprom: Eeprom,
+}
+
+impl Mbc7 {
+    pub fn new() -> Self {
+        Mbc7 {
+            ram_enabled: false,
+            registers_enabled: false,
+            rom_bank: 1,
+            tilt: (0.0, 0.0),
+            latched: (0x8000, 0x8000),
+            erased: false,
+            eeprom: Eeprom::new(),
+        }
+    }
+
+    fn enabled(&self) -> bool {
+        self.ram_enabled && self.registers_enabled
+    }
+}
+
+impl Mbc for Mbc7 {
+    fn rom_bank(&self, address: u16) -> usize {
+        match address {
+            0x0000..=0x3FFF => 0,
+            _ => self.rom_bank as usize,
+        }
+    }
+
+    fn write_register(&mut self, address: u16, value: u8) {
+        match address {
+            0x0000..=0x1FFF => self.ram_enabled = value == 0x0A,
+            0x2000..=0x3FFF => self.rom_bank = value & 0x7F,
+            0x4000..=0x5FFF => self.registers_enabled = value == 0x40,
+            _ => (),
+        }
+    }
+
+    fn read_ram(&self, _ram: &[[u8; 0x2000]], address: u16) -> u8 {
+        if !self.enabled() || address >= 0xB000 {
+            return 0xFF;
+        }
+
+        match (address >> 4) & 0x0F {
+            0x2 => self.latched.0 as u8,
+            0x3 => (self.latched.0 >> 8) as u8,
+            0x4 => self.latched.1 as u8,
+            0x5 => (self.latched.1 >> 8) as u8,
+            0x6 => 0x00,
+            0x8 => self.eeprom.read(),
+            _ => 0xFF,
+        }
+    }
+
+    fn write_ram(&mut self, ram: &mut [[u8; 0x2000]], address: u16, value: u8) {
+        if !self.enabled() || address >= 0xB000 {
+            return;
+        }
+
+        match (address >> 4) & 0x0F {
+            0x0 if value == 0x55 => {
+                self.latched = (0x8000, 0x8000);
+                self.erased = true;
+            }
+            0x1 if value == 0xAA && self.erased => {
+                let (x, y) = self.tilt;
+                self.latched = (
+                    (ACCELEROMETER_CENTER + ACCELEROMETER_G * x) as u16,
+                    (ACCELEROMETER_CENTER + ACCELEROMETER_G * y) as u16,
+                );
+                self.erased = false;
+            }
+            0x8 => self.eeprom.write(&mut ram[0][..EEPROM_WORDS * 2], value),
+            _ => (),
+        }
+    }
+
+    // The EEPROM sits behind a serial port, not on the bus
+    fn ram_location(&self, _ram: &[[u8; 0x2000]], _address: u16) -> Option<(usize, usize)> {
+        None
+    }
+
+    fn set_tilt(&mut self, x: f32, y: f32) {
+        self.tilt = (x, y);
+    }
+
+    fn save(&self, ram: &[[u8; 0x2000]]) -> Vec<u8> {
+        ram[0][..EEPROM_WORDS * 2].to_vec()
+    }
+
+    fn load(&mut self, ram: &mut [[u8; 0x2000]], data: &[u8]) {
+        for (byte, value) in ram[0][..EEPROM_WORDS * 2].iter_mut().zip(data) {
+            *byte = *value;
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::bus::Bus;
+    use crate::cartridge::test::test_cartridge;
+    use crate::cartridge::Cartridge;
+
+    fn cartridge() -> Cartridge {
+        let mut cartridge = test_cartridge(64, 1, Mbc7::new());
+        cartridge.battery = true;
+        cartridge.write(0x0000, 0x0A);
+        cartridge.write(0x4000, 0x40);
+        cartridge
+    }
+
+    // Clock one bit into the EEPROM with CS held high
+    fn send_bit(cartridge: &mut Cartridge, bit: bool) -> bool {
+        let data_in = (bit as u8) << 1;
+        cartridge.write(0xA080, 0x80 | data_in);
+        cartridge.write(0xA080, 0xC0 | data_in);
+        cartridge.read(0xA080) & 0x01 != 0
+    }
+
+    fn send(cartridge: &mut Cartridge, bits: u32, count: u8) {
+        for i in (0..count).rev() {
+            send_bit(cartridge, bits >> i & 1 != 0);
+        }
+    }
+
+    fn release(cartridge: &mut Cartridge) {
+        cartridge.write(0xA080, 0x00);
+    }
+
+    fn command(cartridge: &mut Cartridge, opcode: u32, address: u32) {
+        send(cartridge, 1 << 10 | opcode << 8 | address, 11);
+    }
+
+    fn read_word(cartridge: &mut Cartridge, address: u32) -> u16 {
+        command(cartridge, 0b10, address);
+        assert_eq!(cartridge.read(0xA080) & 0x01, 0);
+
+        let mut word = 0;
+        for _ in 0..16 {
+            word = word << 1 | send_bit(cartridge, false) as u16;
+        }
+        release(cartridge);
+        word
+    }
+
+    fn write_word(cartridge: &mut Cartridge, address: u32, value: u16) {
+        command(cartridge, 0b01, address);
+        send(cartridge, value as u32, 16);
+        release(cartridge);
+    }
+
+    #[test]
+    fn accelerometer_latch_works() {
+        let mut cartridge = cartridge();
+        cartridge.set_tilt(0.5, -1.0);
+
+        // Latching needs an erase first
+        cartridge.write(0xA010, 0xAA);
+        assert_eq!(cartridge.read(0xA020), 0x00);
+        assert_eq!(cartridge.read(0xA030), 0x80);
+
+        cartridge.write(0xA000, 0x55);
+        cartridge.write(0xA010, 0xAA);
+        assert_eq!(cartridge.read(0xA020), 0x08);
+        assert_eq!(cartridge.read(0xA030), 0x82);
+        assert_eq!(cartridge.read(0xA040), 0x60);
+        assert_eq!(cartridge.read(0xA050), 0x81);
+
+        // Holds its value until erased and latched again
+        cartridge.set_tilt(0.0, 0.0);
+        cartridge.write(0xA010, 0xAA);
+        assert_eq!(cartridge.read(0xA020), 0x08);
+
+        cartridge.write(0x4000, 0x00);
+        assert_eq!(cartridge.read(0xA020), 0xFF);
+    }
+
+    #[test]
+    fn poke_skips_the_registers() {
+        let mut cartridge = cartridge();
+        cartridge.set_tilt(0.5, -1.0);
+        let save = cartridge.save_data();
+
+        // An erase and latch through poke would move the accelerometer
+        cartridge.poke(0xA000, 0x55);
+        cartridge.poke(0xA010, 0xAA);
+        cartridge.poke(0xA080, 0xFF);
+        assert_eq!(cartridge.peek(0xA020), 0x00);
+        assert_eq!(cartridge.peek(0xA030), 0x80);
+        assert_eq!(cartridge.save_data(), save);
+    }
+
+    #[test]
+    fn eeprom_write_protect_works() {
+        let mut cartridge = cartridge();
+
+        write_word(&mut cartridge, 0x05, 0x1234);
+        assert_eq!(read_word(&mut cartridge, 0x05), 0x0000);
+
+        // EWEN
+        command(&mut cartridge, 0b00, 0xC0);
+        release(&mut cartridge);
+        command(&mut cartridge, 0b01, 0x05);
+        send(&mut cartridge, 0x1234, 16);
+        // DO reports ready once the write is done
+        assert_eq!(cartridge.read(0xA080) & 0x01, 0x01);
+        release(&mut cartridge);
+        assert_eq!(read_word(&mut cartridge, 0x05), 0x1234);
+
+        // EWDS
+        command(&mut cartridge, 0b00, 0x00);
+        release(&mut cartridge);
+        write_word(&mut cartridge, 0x05, 0xABCD);
+        assert_eq!(read_word(&mut cartridge, 0x05), 0x1234);
+    }
+
+    #[test]
+    fn eeprom_erase_works() {
+        let mut cartridge = cartridge();
+        command(&mut cartridge, 0b00, 0xC0);
+        release(&mut cartridge);
+
+        // WRAL
+        command(&mut cartridge, 0b00, 0x40);
+        send(&mut cartridge, 0x5A5A, 16);
+        release(&mut cartridge);
+        assert_eq!(read_word(&mut cartridge, 0x00), 0x5A5A);
+        assert_eq!(read_word(&mut cartridge, 0x7F), 0x5A5A);
+
+        // ERASE
+        command(&mut cartridge, 0b11, 0x10);
+        release(&mut cartridge);
+        assert_eq!(read_word(&mut cartridge, 0x10), 0xFFFF);
+        assert_eq!(read_word(&mut cartridge, 0x11), 0x5A5A);
+
+        // ERAL
+        command(&mut cartridge, 0b00, 0x80);
+        release(&mut cartridge);
+        assert_eq!(read_word(&mut cartridge, 0x11), 0xFFFF);
+    }
+
+    #[test]
+    fn eeprom_is_saved() {
+        let mut cartridge = cartridge();
+        command(&mut cartridge, 0b00, 0xC0);
+        release(&mut cartridge);
+        write_word(&mut cartridge, 0x7F, 0xBEEF);
+
+        let save = cartridge.save_data().unwrap();
+        assert_eq!(save.len(), EEPROM_WORDS * 2);
+
+        let mut restored = Cartridge::new(64, 1);
+        restored.mbc = Box::new(Mbc7::new());
+        restored.write(0x0000, 0x0A);
+        restored.write(0x4000, 0x40);
+        restored.load_save_data(&save);
+        assert_eq!(read_word(&mut restored, 0x7F), 0xBEEF);
+    }
+}
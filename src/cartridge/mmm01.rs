@@ -0,0 +1,244 @@
+use crate::cartridge::Mbc;
+
+// https://gbdev.io/pandocs/MMM01.html
+/* [0000, 1FFF] RAM Enable - bits 4~5 RAM bank mask and bit 6 maps the game, while unmapped
+ * [2000, 3FFF] ROM Bank Number - bits 0~4, bits 5~6 while unmapped
+ * [4000, 5FFF] RAM Bank Number - bits 0~1, while unmapped also
+ *              bits 2~3 upper RAM bank, bits 4~5 ROM bank bits 7~8, bit 6 locks the mode
+ * [6000, 7FFF] Banking Mode Select - bit 0, bits 2~5 ROM bank mask while unmapped
+ */
+
+pub struct Mmm01 {
+    // Starts unmapped showing the menu in the last 32 KiB
+    mapped: bool,
+    ram_enabled: bool,
+    // 9 bits, the menu picks the upper ones and the game the rest
+    rom_bank: u16,
+    // ROM bank bits 1~4 the game can no longer change
+    rom_mask: u16,
+    ram_bank: u8,
+    // RAM bank bits 0~1 the game can no longer change
+    ram_mask: u8,
+    advanced_mode: bool,
+    mode_locked: bool,
+}
+
+impl Mmm01 {
+    pub fn new() -> Self {
+        Mmm01 {
+            mapped: false,
+            ram_enabled: false,
+            rom_bank: 0,
+            rom_mask: 0,
+            ram_bank: 0,
+            ram_mask: 0,
+            advanced_mode: false,
+            mode_locked: false,
+        }
+    }
+
+    // Bits of the 5 bit ROM bank register the game still drives
+    fn rom_bits(&self) -> u16 {
+        0x1F & !self.rom_mask
+    }
+
+    fn ram_bits(&self) -> u8 {
+        0x03 & !self.ram_mask
+    }
+}
+
+impl Mbc for Mmm01 {
+    fn rom_bank(&self, address: u16) -> usize {
+        if !self.mapped {
+            // Every bank line is pulled high but A14
+            return 0x1FE | (address >> 14) as usize;
+        }
+
+        match address {
+            0x0000..=0x3FFF => (self.rom_bank & !self.rom_bits()) as usize,
+            _ if self.rom_bank & self.rom_bits() == 0 => (self.rom_bank | 0x01) as usize,
+            _ => self.rom_bank as usize,
+        }
+    }
+
+    fn write_register(&mut self, address: u16, value: u8) {
+        match address {
+            0x0000..=0x1FFF => {
+                self.ram_enabled = value & 0x0F == 0x0A;
+                if !self.mapped {
+                    self.ram_mask = (value >> 4) & 0x03;
+                    self.mapped = value & 0x40 != 0;
+                }
+            }
+            0x2000..=0x3FFF => {
+                let bits = self.rom_bits();
+                self.rom_bank = (self.rom_bank & !bits) | (value as u16 & bits);
+                if !self.mapped {
+                    self.rom_bank = (self.rom_bank & !0x60) | (value as u16 & 0x60);
+                }
+            }
+            0x4000..=0x5FFF => {
+                let bits = self.ram_bits();
+                self.ram_bank = (self.ram_bank & !bits) | (value & bits);
+                if !self.mapped {
+                    self.ram_bank = (self.ram_bank & 0x03) | (value & 0x0C);
+                    self.rom_bank = (self.rom_bank & 0x7F) | ((value as u16 & 0x30) << 3);
+                    self.mode_locked = value & 0x40 != 0;
+                }
+            }
+            _ => {
+                if !self.mapped || !self.mode_locked {
+                    self.advanced_mode = value & 0x01 != 0;
+                }
+                if !self.mapped {
+                    self.rom_mask = (value as u16 >> 1) & 0x1E;
+                }
+            }
+        }
+    }
+
+    fn read_ram(&self, ram: &[[u8; 0x2000]], address: u16) -> u8 {
+        match self.ram_location(ram, address) {
+            Some((bank, offset)) if self.ram_enabled => ram[bank][offset],
+            _ => 0xFF,
+        }
+    }
+
+    fn write_ram(&mut self, ram: &mut [[u8; 0x2000]], address: u16, value: u8) {
+        match self.ram_location(ram, address) {
+            Some((bank, offset)) if self.ram_enabled => ram[bank][offset] = value,
+            _ => (),
+        }
+    }
+
+    fn ram_location(&self, ram: &[[u8; 0x2000]], address: u16) -> Option<(usize, usize)> {
+        if ram.is_empty() {
+            return None;
+        }
+
+        let bank = match self.advanced_mode {
+            true => self.ram_bank,
+            false => self.ram_bank & !self.ram_bits(),
+        };
+        Some((bank as usize % ram.len(), address as usize & 0x1FFF))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::bus::Bus;
+    use crate::cartridge::test::{assert_poke_matches_write, test_cartridge};
+    use crate::cartridge::{header_checksum, Cartridge, CartridgeError, CartridgeHeader, LoadMode};
+
+    fn cartridge() -> Cartridge {
+        test_cartridge(64, 4, Mmm01::new())
+    }
+
+    #[test]
+    fn menu_is_last_32_kib() {
+        let mut cartridge = cartridge();
+
+        assert_eq!(cartridge.read(0x0000), 62);
+        assert_eq!(cartridge.read(0x4000), 63);
+
+        // Bank writes before mapping don't move the menu
+        cartridge.write(0x2000, 0x05);
+        assert_eq!(cartridge.read(0x4000), 63);
+    }
+
+    #[test]
+    fn game_is_locked_in() {
+        let mut cartridge = cartridge();
+
+        // 128 KiB game starting at bank 0x30
+        cartridge.write(0x2000, 0x30);
+        cartridge.write(0x6000, 0x30);
+        cartridge.write(0x0000, 0x40);
+
+        assert_eq!(cartridge.read(0x0000), 0x30);
+        assert_eq!(cartridge.read(0x4000), 0x31);
+
+        cartridge.write(0x2000, 0x05);
+        assert_eq!(cartridge.read(0x4000), 0x35);
+
+        // The game can't leave its window
+        cartridge.write(0x2000, 0x1F);
+        assert_eq!(cartridge.read(0x4000), 0x37);
+        cartridge.write(0x2000, 0x00);
+        assert_eq!(cartridge.read(0x4000), 0x31);
+        assert_eq!(cartridge.read(0x0000), 0x30);
+
+        // Mapping registers are locked too
+        cartridge.write(0x0000, 0x00);
+        cartridge.write(0x6000, 0x00);
+        cartridge.write(0x2000, 0x02);
+        assert_eq!(cartridge.read(0x4000), 0x32);
+    }
+
+    #[test]
+    fn header_is_found_at_the_end() {
+        let mut bytes = vec![0; 0x20000];
+        bytes[0x0147] = 0x01;
+
+        let menu = 0x18000;
+        bytes[menu] = 0xAB;
+        bytes[menu + 0x0147] = 0x0D;
+        bytes[menu + 0x0148] = 0x02;
+        bytes[menu + 0x014D] = header_checksum(&bytes[menu..]);
+
+        assert_eq!(CartridgeHeader::locate(&bytes), menu);
+
+        let (mut cartridge, _) = Cartridge::from_bytes(bytes.clone(), LoadMode::Lenient).unwrap();
+        assert!(cartridge.save_data().is_some());
+        assert_eq!(cartridge.read(0x0000), 0xAB);
+
+        // Size problems report the codes from the header that was parsed
+        bytes[menu + 0x0148] = 0x03;
+        bytes[menu + 0x0149] = 0x07;
+        bytes[menu + 0x014D] = header_checksum(&bytes[menu..]);
+        let header = CartridgeHeader::parse(&bytes[menu..]).unwrap();
+        let problems = header.verify(&bytes);
+        assert!(problems.iter().any(|problem| matches!(problem, CartridgeError::RomSize { code: 0x03, .. })));
+        assert!(problems.iter().any(|problem| matches!(problem, CartridgeError::InvalidRamSize(0x07))));
+    }
+
+    #[test]
+    fn ram_works_like_mbc1() {
+        let mut cartridge = cartridge();
+        cartridge.write(0x0000, 0x40);
+
+        cartridge.write(0xA000, 0xAB);
+        assert_eq!(cartridge.read(0xA000), 0xFF);
+
+        cartridge.write(0x0000, 0x0A);
+        cartridge.write(0x6000, 0x01);
+        for bank in 0..4 {
+            cartridge.write(0x4000, bank);
+            cartridge.write(0xA000, bank + 0x10);
+        }
+
+        cartridge.write(0x4000, 0x02);
+        assert_eq!(cartridge.read(0xA000), 0x12);
+
+        cartridge.write(0x6000, 0x00);
+        assert_eq!(cartridge.read(0xA000), 0x10);
+    }
+
+    #[test]
+    fn poke_reaches_mapped_ram() {
+        let mut cartridge = cartridge();
+        cartridge.write(0x6000, 0x01);
+        cartridge.write(0x4000, 0x02);
+        cartridge.write(0x0000, 0x40);
+
+        // Disabled RAM still takes the value in the mapped bank
+        cartridge.poke(0xB000, 0xAB);
+        cartridge.write(0x0000, 0x0A);
+        assert_eq!(cartridge.read(0xB000), 0xAB);
+        assert_poke_matches_write(&mut cartridge, 0xA000);
+
+        cartridge.write(0x4000, 0x01);
+        assert_eq!(cartridge.read(0xB000), 0x00);
+    }
+}
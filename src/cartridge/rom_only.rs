@@ -0,0 +1,32 @@
+use crate::cartridge::Mbc;
+
+/// No controller, 32 KiB of rom and up to 8 KiB of ram wired straight to the bus
+pub struct RomOnly;
+
+impl Mbc for RomOnly {
+    fn rom_bank(&self, address: u16) -> usize {
+        (address >> 14) as usize
+    }
+
+    fn write_register(&mut self, _address: u16, _value: u8) {
+        // ROM is read only
+    }
+
+    fn read_ram(&self, ram: &[[u8; 0x2000]], address: u16) -> u8 {
+        match self.ram_location(ram, address) {
+            Some((bank, offset)) => ram[bank][offset],
+            // Open bus when there is no RAM to answer
+            None => 0xFF,
+        }
+    }
+
+    fn write_ram(&mut self, ram: &mut [[u8; 0x2000]], address: u16, value: u8) {
+        if let Some((bank, offset)) = self.ram_location(ram, address) {
+            ram[bank][offset] = value;
+        }
+    }
+
+    fn ram_location(&self, ram: &[[u8; 0x2000]], address: u16) -> Option<(usize, usize)> {
+        (!ram.is_empty()).then_some((0, address as usize & 0x1FFF))
+    }
+}
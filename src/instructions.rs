@@ -1,4 +1,4 @@
-use crate::{Processor, processor::Register, processor::Flag};
+use crate::{Processor, processor::Register, processor::RegisterPair, processor::Flag, processor::State, processor::DIVIDER};
 
 pub fn to_u16(b0: u8, b1: u8) -> u16 {
     ((b0 as u16) << 8) | b1 as u16
@@ -12,6 +12,18 @@ pub struct Instruction {
     pub operation: fn(&mut Processor, u16),
     pub bytes: u8,
     pub cycles: u8,
+    // Cycles used instead when a conditional branch is taken
+    pub cycles_taken: u8,
+}
+
+impl Instruction {
+    pub const fn new(operation: fn(&mut Processor, u16), bytes: u8, cycles: u8) -> Self {
+        Instruction { operation, bytes, cycles, cycles_taken: cycles }
+    }
+
+    pub const fn branch(operation: fn(&mut Processor, u16), bytes: u8, cycles: u8, cycles_taken: u8) -> Self {
+        Instruction { operation, bytes, cycles, cycles_taken }
+    }
 }
 
 // !!! LITTLE ENDIAN !!!
@@ -28,96 +40,626 @@ pub struct Instruction {
 // ld -> load operation
 // inc -> increase by 1 operation
 // dec -> decrease by 1 operation
+// bit -> test bit operation
+// res -> reset bit operation
+// set -> set bit operation
+//
+// The u16 handed to every operation holds the operand bytes in fetch order:
+// the first byte after the opcode in the high byte, the second in the low byte.
+
+pub const INSTRUCTIONS: [Instruction; 256] = [
+    // 0x0_
+    Instruction::new(nop, 1, 4),
+    Instruction::new(ld_bc_n16, 3, 12),
+    Instruction::new(ld_bcp_a, 1, 8),
+    Instruction::new(inc_bc, 1, 8),
+    Instruction::new(inc_b, 1, 4),
+    Instruction::new(dec_b, 1, 4),
+    Instruction::new(ld_b_n8, 2, 8),
+    Instruction::new(rlca, 1, 4),
+    Instruction::new(ld_n16_sp, 3, 20),
+    Instruction::new(add_hl_bc, 1, 8),
+    Instruction::new(ld_a_bcp, 1, 8),
+    Instruction::new(dec_bc, 1, 8),
+    Instruction::new(inc_c, 1, 4),
+    Instruction::new(dec_c, 1, 4),
+    Instruction::new(ld_c_n8, 2, 8),
+    Instruction::new(rrca, 1, 4),
+    // 0x1_
+    Instruction::new(stop, 2, 4),
+    Instruction::new(ld_de_n16, 3, 12),
+    Instruction::new(ld_dep_a, 1, 8),
+    Instruction::new(inc_de, 1, 8),
+    Instruction::new(inc_d, 1, 4),
+    Instruction::new(dec_d, 1, 4),
+    Instruction::new(ld_d_n8, 2, 8),
+    Instruction::new(rla, 1, 4),
+    Instruction::new(jr_e8, 2, 12),
+    Instruction::new(add_hl_de, 1, 8),
+    Instruction::new(ld_a_dep, 1, 8),
+    Instruction::new(dec_de, 1, 8),
+    Instruction::new(inc_e, 1, 4),
+    Instruction::new(dec_e, 1, 4),
+    Instruction::new(ld_e_n8, 2, 8),
+    Instruction::new(rra, 1, 4),
+    // 0x2_
+    Instruction::branch(jr_nz_e8, 2, 8, 12),
+    Instruction::new(ld_hl_n16, 3, 12),
+    Instruction::new(ld_hli_a, 1, 8),
+    Instruction::new(inc_hl, 1, 8),
+    Instruction::new(inc_h, 1, 4),
+    Instruction::new(dec_h, 1, 4),
+    Instruction::new(ld_h_n8, 2, 8),
+    Instruction::new(daa, 1, 4),
+    Instruction::branch(jr_z_e8, 2, 8, 12),
+    Instruction::new(add_hl_hl, 1, 8),
+    Instruction::new(ld_a_hli, 1, 8),
+    Instruction::new(dec_hl, 1, 8),
+    Instruction::new(inc_l, 1, 4),
+    Instruction::new(dec_l, 1, 4),
+    Instruction::new(ld_l_n8, 2, 8),
+    Instruction::new(cpl, 1, 4),
+    // 0x3_
+    Instruction::branch(jr_nc_e8, 2, 8, 12),
+    Instruction::new(ld_sp_n16, 3, 12),
+    Instruction::new(ld_hld_a, 1, 8),
+    Instruction::new(inc_sp, 1, 8),
+    Instruction::new(inc_hlp, 1, 12),
+    Instruction::new(dec_hlp, 1, 12),
+    Instruction::new(ld_hlp_n8, 2, 12),
+    Instruction::new(scf, 1, 4),
+    Instruction::branch(jr_c_e8, 2, 8, 12),
+    Instruction::new(add_hl_sp, 1, 8),
+    Instruction::new(ld_a_hld, 1, 8),
+    Instruction::new(dec_sp, 1, 8),
+    Instruction::new(inc_a, 1, 4),
+    Instruction::new(dec_a, 1, 4),
+    Instruction::new(ld_a_n8, 2, 8),
+    Instruction::new(ccf, 1, 4),
+    // 0x4_
+    Instruction::new(ld_b_b, 1, 4),
+    Instruction::new(ld_b_c, 1, 4),
+    Instruction::new(ld_b_d, 1, 4),
+    Instruction::new(ld_b_e, 1, 4),
+    Instruction::new(ld_b_h, 1, 4),
+    Instruction::new(ld_b_l, 1, 4),
+    Instruction::new(ld_b_hlp, 1, 8),
+    Instruction::new(ld_b_a, 1, 4),
+    Instruction::new(ld_c_b, 1, 4),
+    Instruction::new(ld_c_c, 1, 4),
+    Instruction::new(ld_c_d, 1, 4),
+    Instruction::new(ld_c_e, 1, 4),
+    Instruction::new(ld_c_h, 1, 4),
+    Instruction::new(ld_c_l, 1, 4),
+    Instruction::new(ld_c_hlp, 1, 8),
+    Instruction::new(ld_c_a, 1, 4),
+    // 0x5_
+    Instruction::new(ld_d_b, 1, 4),
+    Instruction::new(ld_d_c, 1, 4),
+    Instruction::new(ld_d_d, 1, 4),
+    Instruction::new(ld_d_e, 1, 4),
+    Instruction::new(ld_d_h, 1, 4),
+    Instruction::new(ld_d_l, 1, 4),
+    Instruction::new(ld_d_hlp, 1, 8),
+    Instruction::new(ld_d_a, 1, 4),
+    Instruction::new(ld_e_b, 1, 4),
+    Instruction::new(ld_e_c, 1, 4),
+    Instruction::new(ld_e_d, 1, 4),
+    Instruction::new(ld_e_e, 1, 4),
+    Instruction::new(ld_e_h, 1, 4),
+    Instruction::new(ld_e_l, 1, 4),
+    Instruction::new(ld_e_hlp, 1, 8),
+    Instruction::new(ld_e_a, 1, 4),
+    // 0x6_
+    Instruction::new(ld_h_b, 1, 4),
+    Instruction::new(ld_h_c, 1, 4),
+    Instruction::new(ld_h_d, 1, 4),
+    Instruction::new(ld_h_e, 1, 4),
+    Instruction::new(ld_h_h, 1, 4),
+    Instruction::new(ld_h_l, 1, 4),
+    Instruction::new(ld_h_hlp, 1, 8),
+    Instruction::new(ld_h_a, 1, 4),
+    Instruction::new(ld_l_b, 1, 4),
+    Instruction::new(ld_l_c, 1, 4),
+    Instruction::new(ld_l_d, 1, 4),
+    Instruction::new(ld_l_e, 1, 4),
+    Instruction::new(ld_l_h, 1, 4),
+    Instruction::new(ld_l_l, 1, 4),
+    Instruction::new(ld_l_hlp, 1, 8),
+    Instruction::new(ld_l_a, 1, 4),
+    // 0x7_
+    Instruction::new(ld_hlp_b, 1, 8),
+    Instruction::new(ld_hlp_c, 1, 8),
+    Instruction::new(ld_hlp_d, 1, 8),
+    Instruction::new(ld_hlp_e, 1, 8),
+    Instruction::new(ld_hlp_h, 1, 8),
+    Instruction::new(ld_hlp_l, 1, 8),
+    Instruction::new(halt, 1, 4),
+    Instruction::new(ld_hlp_a, 1, 8),
+    Instruction::new(ld_a_b, 1, 4),
+    Instruction::new(ld_a_c, 1, 4),
+    Instruction::new(ld_a_d, 1, 4),
+    Instruction::new(ld_a_e, 1, 4),
+    Instruction::new(ld_a_h, 1, 4),
+    Instruction::new(ld_a_l, 1, 4),
+    Instruction::new(ld_a_hlp, 1, 8),
+    Instruction::new(ld_a_a, 1, 4),
+    // 0x8_
+    Instruction::new(add_a_b, 1, 4),
+    Instruction::new(add_a_c, 1, 4),
+    Instruction::new(add_a_d, 1, 4),
+    Instruction::new(add_a_e, 1, 4),
+    Instruction::new(add_a_h, 1, 4),
+    Instruction::new(add_a_l, 1, 4),
+    Instruction::new(add_a_hlp, 1, 8),
+    Instruction::new(add_a_a, 1, 4),
+    Instruction::new(adc_a_b, 1, 4),
+    Instruction::new(adc_a_c, 1, 4),
+    Instruction::new(adc_a_d, 1, 4),
+    Instruction::new(adc_a_e, 1, 4),
+    Instruction::new(adc_a_h, 1, 4),
+    Instruction::new(adc_a_l, 1, 4),
+    Instruction::new(adc_a_hlp, 1, 8),
+    Instruction::new(adc_a_a, 1, 4),
+    // 0x9_
+    Instruction::new(sub_a_b, 1, 4),
+    Instruction::new(sub_a_c, 1, 4),
+    Instruction::new(sub_a_d, 1, 4),
+    Instruction::new(sub_a_e, 1, 4),
+    Instruction::new(sub_a_h, 1, 4),
+    Instruction::new(sub_a_l, 1, 4),
+    Instruction::new(sub_a_hlp, 1, 8),
+    Instruction::new(sub_a_a, 1, 4),
+    Instruction::new(sbc_a_b, 1, 4),
+    Instruction::new(sbc_a_c, 1, 4),
+    Instruction::new(sbc_a_d, 1, 4),
+    Instruction::new(sbc_a_e, 1, 4),
+    Instruction::new(sbc_a_h, 1, 4),
+    Instruction::new(sbc_a_l, 1, 4),
+    Instruction::new(sbc_a_hlp, 1, 8),
+    Instruction::new(sbc_a_a, 1, 4),
+    // 0xA_
+    Instruction::new(and_a_b, 1, 4),
+    Instruction::new(and_a_c, 1, 4),
+    Instruction::new(and_a_d, 1, 4),
+    Instruction::new(and_a_e, 1, 4),
+    Instruction::new(and_a_h, 1, 4),
+    Instruction::new(and_a_l, 1, 4),
+    Instruction::new(and_a_hlp, 1, 8),
+    Instruction::new(and_a_a, 1, 4),
+    Instruction::new(xor_a_b, 1, 4),
+    Instruction::new(xor_a_c, 1, 4),
+    Instruction::new(xor_a_d, 1, 4),
+    Instruction::new(xor_a_e, 1, 4),
+    Instruction::new(xor_a_h, 1, 4),
+    Instruction::new(xor_a_l, 1, 4),
+    Instruction::new(xor_a_hlp, 1, 8),
+    Instruction::new(xor_a_a, 1, 4),
+    // 0xB_
+    Instruction::new(or_a_b, 1, 4),
+    Instruction::new(or_a_c, 1, 4),
+    Instruction::new(or_a_d, 1, 4),
+    Instruction::new(or_a_e, 1, 4),
+    Instruction::new(or_a_h, 1, 4),
+    Instruction::new(or_a_l, 1, 4),
+    Instruction::new(or_a_hlp, 1, 8),
+    Instruction::new(or_a_a, 1, 4),
+    Instruction::new(cp_a_b, 1, 4),
+    Instruction::new(cp_a_c, 1, 4),
+    Instruction::new(cp_a_d, 1, 4),
+    Instruction::new(cp_a_e, 1, 4),
+    Instruction::new(cp_a_h, 1, 4),
+    Instruction::new(cp_a_l, 1, 4),
+    Instruction::new(cp_a_hlp, 1, 8),
+    Instruction::new(cp_a_a, 1, 4),
+    // 0xC_
+    Instruction::branch(ret_nz, 1, 8, 20),
+    Instruction::new(pop_bc, 1, 12),
+    Instruction::branch(jp_nz_n16, 3, 12, 16),
+    Instruction::new(jp_n16, 3, 16),
+    Instruction::branch(call_nz_n16, 3, 12, 24),
+    Instruction::new(push_bc, 1, 16),
+    Instruction::new(add_a_n8, 2, 8),
+    Instruction::new(rst_00, 1, 16),
+    Instruction::branch(ret_z, 1, 8, 20),
+    Instruction::new(ret, 1, 16),
+    Instruction::branch(jp_z_n16, 3, 12, 16),
+    Instruction::new(prefix_cb, 2, 8),
+    Instruction::branch(call_z_n16, 3, 12, 24),
+    Instruction::new(call_n16, 3, 24),
+    Instruction::new(adc_a_n8, 2, 8),
+    Instruction::new(rst_08, 1, 16),
+    // 0xD_
+    Instruction::branch(ret_nc, 1, 8, 20),
+    Instruction::new(pop_de, 1, 12),
+    Instruction::branch(jp_nc_n16, 3, 12, 16),
+    Instruction::new(illegal, 1, 4),
+    Instruction::branch(call_nc_n16, 3, 12, 24),
+    Instruction::new(push_de, 1, 16),
+    Instruction::new(sub_a_n8, 2, 8),
+    Instruction::new(rst_10, 1, 16),
+    Instruction::branch(ret_c, 1, 8, 20),
+    Instruction::new(reti, 1, 16),
+    Instruction::branch(jp_c_n16, 3, 12, 16),
+    Instruction::new(illegal, 1, 4),
+    Instruction::branch(call_c_n16, 3, 12, 24),
+    Instruction::new(illegal, 1, 4),
+    Instruction::new(sbc_a_n8, 2, 8),
+    Instruction::new(rst_18, 1, 16),
+    // 0xE_
+    Instruction::new(ldh_r8_a, 2, 12),
+    Instruction::new(pop_hl, 1, 12),
+    Instruction::new(ldh_c_a, 1, 8),
+    Instruction::new(illegal, 1, 4),
+    Instruction::new(illegal, 1, 4),
+    Instruction::new(push_hl, 1, 16),
+    Instruction::new(and_a_n8, 2, 8),
+    Instruction::new(rst_20, 1, 16),
+    Instruction::new(add_sp_e8, 2, 16),
+    Instruction::new(jp_hl, 1, 4),
+    Instruction::new(ld_r16_a, 3, 16),
+    Instruction::new(illegal, 1, 4),
+    Instruction::new(illegal, 1, 4),
+    Instruction::new(illegal, 1, 4),
+    Instruction::new(xor_a_n8, 2, 8),
+    Instruction::new(rst_28, 1, 16),
+    // 0xF_
+    Instruction::new(ldh_a_r8, 2, 12),
+    Instruction::new(pop_af, 1, 12),
+    Instruction::new(ldh_a_c, 1, 8),
+    Instruction::new(di, 1, 4),
+    Instruction::new(illegal, 1, 4),
+    Instruction::new(push_af, 1, 16),
+    Instruction::new(or_a_n8, 2, 8),
+    Instruction::new(rst_30, 1, 16),
+    Instruction::new(ld_hl_sp_e8, 2, 12),
+    Instruction::new(ld_sp_hl, 1, 8),
+    Instruction::new(ld_a_r16, 3, 16),
+    Instruction::new(ei, 1, 4),
+    Instruction::new(illegal, 1, 4),
+    Instruction::new(illegal, 1, 4),
+    Instruction::new(cp_a_n8, 2, 8),
+    Instruction::new(rst_38, 1, 16),
+];
 
-/*
-const INSTRUCTIONS: [Instruction; 256] = 
-[
-    Instruction { operation: nop, 1, 4 },
-    Instruction { operation: ld_bc_nn, 3, 12 },
-    Instruction { operation: ld_bc_a, 1, 8 },
-    Instruction { operation: inc_bc, 1, 8 },
-    Instruction { operation: inc_b, 1, 8 },
-    Instruction { operation: dec_b, 1, 4 },
-    Instruction { operation: ld_b_n, 2, 8 },
-    Instruction { operation: rlca, 1, 4 },
-    Instruction { operation: ld_n16_sp, 3, 20 },
-    Instruction { operation: add_hl_bc, 1, 8 },
-    Instruction { operation: ld_a_bc,
-    Instruction { operation: dec_bc,
-    Instruction { operation: inc_c,
-    Instruction { operation: dec_c,
-    Instruction { operation: ld_c_n,
-    Instruction { operation: rrca, 
-    Instruction { operation: stop,
-    Instruction { operation: ld_de_nn,
-    Instruction { operation: ld_de_a,
-    Instruction { operation: inc_de, 
-    Instruction { operation: inc_d,
-    Instruction { operation: dec_d,
-    Instruction { operation: ld_d_n, 
-    Instruction { operation: rla,
-    Instruction { operation: jr_e,
-    Instruction { operation: add_hl_de,
-    Instruction { operation: ld_a_de,
-    Instruction { operation: dec_de,
-    Instruction { operation: inc_e,
-    Instruction { operation: dec_e,
-    Instruction { operation: ld_e_n, 
-    Instruction { operation: rra, 
-    Instruction { operation: jr_nz_e,
-    Instruction { operation: ld_hl_nn,
-    Instruction { operation: ld_hli_a,
-    Instruction { operation: inc_hl, 
-    Instruction { operation: inc_h,
-    Instruction { operation: dec_h,
-    Instruction { operation: ld_h_n, 
-    Instruction { operation: daa,
-    Instruction { operation: jr_z_e,
-    Instruction { operation: add_hl_hl,
-    Instruction { operation: ld_a_hlp,
-    Instruction { operation: dec_hl,
-    Instruction { operation: inc_l,
-    Instruction { operation: dec_l,
-    Instruction { operation: ld_l_n, 
-    Instruction { operation: cpl, 
-    Instruction { operation: jr_nc_e,
-    Instruction { operation: ld_sp_nn,
-    Instruction { operation: ld_hld_a,
-    Instruction { operation: inc_sp,
-    Instruction { operation: inc_hl,
-    Instruction { operation: dec_hl,
-    Instruction { operation: ld_hl_n,
-    Instruction { operation: scf,
-    Instruction { operation: jr_c_e,
-    Instruction { operation: add_hl_sp,
-    Instruction { operation: ld_a_hlm,
-    Instruction { operation: dec_sp,
-    Instruction { operation: inc_a,
-    Instruction { operation: dec_a,
-    Instruction { operation: ld_a_n, 
-    Instruction { operation: cff, 
+// Opcodes following the 0xCB prefix, cycles include the prefix fetch
+pub const CB_INSTRUCTIONS: [Instruction; 256] = [
+    // 0x0_
+    Instruction::new(rlc_b, 2, 8),
+    Instruction::new(rlc_c, 2, 8),
+    Instruction::new(rlc_d, 2, 8),
+    Instruction::new(rlc_e, 2, 8),
+    Instruction::new(rlc_h, 2, 8),
+    Instruction::new(rlc_l, 2, 8),
+    Instruction::new(rlc_hlp, 2, 16),
+    Instruction::new(rlc_a, 2, 8),
+    Instruction::new(rrc_b, 2, 8),
+    Instruction::new(rrc_c, 2, 8),
+    Instruction::new(rrc_d, 2, 8),
+    Instruction::new(rrc_e, 2, 8),
+    Instruction::new(rrc_h, 2, 8),
+    Instruction::new(rrc_l, 2, 8),
+    Instruction::new(rrc_hlp, 2, 16),
+    Instruction::new(rrc_a, 2, 8),
+    // 0x1_
+    Instruction::new(rl_b, 2, 8),
+    Instruction::new(rl_c, 2, 8),
+    Instruction::new(rl_d, 2, 8),
+    Instruction::new(rl_e, 2, 8),
+    Instruction::new(rl_h, 2, 8),
+    Instruction::new(rl_l, 2, 8),
+    Instruction::new(rl_hlp, 2, 16),
+    Instruction::new(rl_a, 2, 8),
+    Instruction::new(rr_b, 2, 8),
+    Instruction::new(rr_c, 2, 8),
+    Instruction::new(rr_d, 2, 8),
+    Instruction::new(rr_e, 2, 8),
+    Instruction::new(rr_h, 2, 8),
+    Instruction::new(rr_l, 2, 8),
+    Instruction::new(rr_hlp, 2, 16),
+    Instruction::new(rr_a, 2, 8),
+    // 0x2_
+    Instruction::new(sla_b, 2, 8),
+    Instruction::new(sla_c, 2, 8),
+    Instruction::new(sla_d, 2, 8),
+    Instruction::new(sla_e, 2, 8),
+    Instruction::new(sla_h, 2, 8),
+    Instruction::new(sla_l, 2, 8),
+    Instruction::new(sla_hlp, 2, 16),
+    Instruction::new(sla_a, 2, 8),
+    Instruction::new(sra_b, 2, 8),
+    Instruction::new(sra_c, 2, 8),
+    Instruction::new(sra_d, 2, 8),
+    Instruction::new(sra_e, 2, 8),
+    Instruction::new(sra_h, 2, 8),
+    Instruction::new(sra_l, 2, 8),
+    Instruction::new(sra_hlp, 2, 16),
+    Instruction::new(sra_a, 2, 8),
+    // 0x3_
+    Instruction::new(swap_b, 2, 8),
+    Instruction::new(swap_c, 2, 8),
+    Instruction::new(swap_d, 2, 8),
+    Instruction::new(swap_e, 2, 8),
+    Instruction::new(swap_h, 2, 8),
+    Instruction::new(swap_l, 2, 8),
+    Instruction::new(swap_hlp, 2, 16),
+    Instruction::new(swap_a, 2, 8),
+    Instruction::new(srl_b, 2, 8),
+    Instruction::new(srl_c, 2, 8),
+    Instruction::new(srl_d, 2, 8),
+    Instruction::new(srl_e, 2, 8),
+    Instruction::new(srl_h, 2, 8),
+    Instruction::new(srl_l, 2, 8),
+    Instruction::new(srl_hlp, 2, 16),
+    Instruction::new(srl_a, 2, 8),
+    // 0x4_
+    Instruction::new(bit_0_b, 2, 8),
+    Instruction::new(bit_0_c, 2, 8),
+    Instruction::new(bit_0_d, 2, 8),
+    Instruction::new(bit_0_e, 2, 8),
+    Instruction::new(bit_0_h, 2, 8),
+    Instruction::new(bit_0_l, 2, 8),
+    Instruction::new(bit_0_hlp, 2, 12),
+    Instruction::new(bit_0_a, 2, 8),
+    Instruction::new(bit_1_b, 2, 8),
+    Instruction::new(bit_1_c, 2, 8),
+    Instruction::new(bit_1_d, 2, 8),
+    Instruction::new(bit_1_e, 2, 8),
+    Instruction::new(bit_1_h, 2, 8),
+    Instruction::new(bit_1_l, 2, 8),
+    Instruction::new(bit_1_hlp, 2, 12),
+    Instruction::new(bit_1_a, 2, 8),
+    // 0x5_
+    Instruction::new(bit_2_b, 2, 8),
+    Instruction::new(bit_2_c, 2, 8),
+    Instruction::new(bit_2_d, 2, 8),
+    Instruction::new(bit_2_e, 2, 8),
+    Instruction::new(bit_2_h, 2, 8),
+    Instruction::new(bit_2_l, 2, 8),
+    Instruction::new(bit_2_hlp, 2, 12),
+    Instruction::new(bit_2_a, 2, 8),
+    Instruction::new(bit_3_b, 2, 8),
+    Instruction::new(bit_3_c, 2, 8),
+    Instruction::new(bit_3_d, 2, 8),
+    Instruction::new(bit_3_e, 2, 8),
+    Instruction::new(bit_3_h, 2, 8),
+    Instruction::new(bit_3_l, 2, 8),
+    Instruction::new(bit_3_hlp, 2, 12),
+    Instruction::new(bit_3_a, 2, 8),
+    // 0x6_
+    Instruction::new(bit_4_b, 2, 8),
+    Instruction::new(bit_4_c, 2, 8),
+    Instruction::new(bit_4_d, 2, 8),
+    Instruction::new(bit_4_e, 2, 8),
+    Instruction::new(bit_4_h, 2, 8),
+    Instruction::new(bit_4_l, 2, 8),
+    Instruction::new(bit_4_hlp, 2, 12),
+    Instruction::new(bit_4_a, 2, 8),
+    Instruction::new(bit_5_b, 2, 8),
+    Instruction::new(bit_5_c, 2, 8),
+    Instruction::new(bit_5_d, 2, 8),
+    Instruction::new(bit_5_e, 2, 8),
+    Instruction::new(bit_5_h, 2, 8),
+    Instruction::new(bit_5_l, 2, 8),
+    Instruction::new(bit_5_hlp, 2, 12),
+    Instruction::new(bit_5_a, 2, 8),
+    // 0x7_
+    Instruction::new(bit_6_b, 2, 8),
+    Instruction::new(bit_6_c, 2, 8),
+    Instruction::new(bit_6_d, 2, 8),
+    Instruction::new(bit_6_e, 2, 8),
+    Instruction::new(bit_6_h, 2, 8),
+    Instruction::new(bit_6_l, 2, 8),
+    Instruction::new(bit_6_hlp, 2, 12),
+    Instruction::new(bit_6_a, 2, 8),
+    Instruction::new(bit_7_b, 2, 8),
+    Instruction::new(bit_7_c, 2, 8),
+    Instruction::new(bit_7_d, 2, 8),
+    Instruction::new(bit_7_e, 2, 8),
+    Instruction::new(bit_7_h, 2, 8),
+    Instruction::new(bit_7_l, 2, 8),
+    Instruction::new(bit_7_hlp, 2, 12),
+    Instruction::new(bit_7_a, 2, 8),
+    // 0x8_
+    Instruction::new(res_0_b, 2, 8),
+    Instruction::new(res_0_c, 2, 8),
+    Instruction::new(res_0_d, 2, 8),
+    Instruction::new(res_0_e, 2, 8),
+    Instruction::new(res_0_h, 2, 8),
+    Instruction::new(res_0_l, 2, 8),
+    Instruction::new(res_0_hlp, 2, 16),
+    Instruction::new(res_0_a, 2, 8),
+    Instruction::new(res_1_b, 2, 8),
+    Instruction::new(res_1_c, 2, 8),
+    Instruction::new(res_1_d, 2, 8),
+    Instruction::new(res_1_e, 2, 8),
+    Instruction::new(res_1_h, 2, 8),
+    Instruction::new(res_1_l, 2, 8),
+    Instruction::new(res_1_hlp, 2, 16),
+    Instruction::new(res_1_a, 2, 8),
+    // 0x9_
+    Instruction::new(res_2_b, 2, 8),
+    Instruction::new(res_2_c, 2, 8),
+    Instruction::new(res_2_d, 2, 8),
+    Instruction::new(res_2_e, 2, 8),
+    Instruction::new(res_2_h, 2, 8),
+    Instruction::new(res_2_l, 2, 8),
+    Instruction::new(res_2_hlp, 2, 16),
+    Instruction::new(res_2_a, 2, 8),
+    Instruction::new(res_3_b, 2, 8),
+    Instruction::new(res_3_c, 2, 8),
+    Instruction::new(res_3_d, 2, 8),
+    Instruction::new(res_3_e, 2, 8),
+    Instruction::new(res_3_h, 2, 8),
+    Instruction::new(res_3_l, 2, 8),
+    Instruction::new(res_3_hlp, 2, 16),
+    Instruction::new(res_3_a, 2, 8),
+    // 0xA_
+    Instruction::new(res_4_b, 2, 8),
+    Instruction::new(res_4_c, 2, 8),
+    Instruction::new(res_4_d, 2, 8),
+    Instruction::new(res_4_e, 2, 8),
+    Instruction::new(res_4_h, 2, 8),
+    Instruction::new(res_4_l, 2, 8),
+    Instruction::new(res_4_hlp, 2, 16),
+    Instruction::new(res_4_a, 2, 8),
+    Instruction::new(res_5_b, 2, 8),
+    Instruction::new(res_5_c, 2, 8),
+    Instruction::new(res_5_d, 2, 8),
+    Instruction::new(res_5_e, 2, 8),
+    Instruction::new(res_5_h, 2, 8),
+    Instruction::new(res_5_l, 2, 8),
+    Instruction::new(res_5_hlp, 2, 16),
+    Instruction::new(res_5_a, 2, 8),
+    // 0xB_
+    Instruction::new(res_6_b, 2, 8),
+    Instruction::new(res_6_c, 2, 8),
+    Instruction::new(res_6_d, 2, 8),
+    Instruction::new(res_6_e, 2, 8),
+    Instruction::new(res_6_h, 2, 8),
+    Instruction::new(res_6_l, 2, 8),
+    Instruction::new(res_6_hlp, 2, 16),
+    Instruction::new(res_6_a, 2, 8),
+    Instruction::new(res_7_b, 2, 8),
+    Instruction::new(res_7_c, 2, 8),
+    Instruction::new(res_7_d, 2, 8),
+    Instruction::new(res_7_e, 2, 8),
+    Instruction::new(res_7_h, 2, 8),
+    Instruction::new(res_7_l, 2, 8),
+    Instruction::new(res_7_hlp, 2, 16),
+    Instruction::new(res_7_a, 2, 8),
+    // 0xC_
+    Instruction::new(set_0_b, 2, 8),
+    Instruction::new(set_0_c, 2, 8),
+    Instruction::new(set_0_d, 2, 8),
+    Instruction::new(set_0_e, 2, 8),
+    Instruction::new(set_0_h, 2, 8),
+    Instruction::new(set_0_l, 2, 8),
+    Instruction::new(set_0_hlp, 2, 16),
+    Instruction::new(set_0_a, 2, 8),
+    Instruction::new(set_1_b, 2, 8),
+    Instruction::new(set_1_c, 2, 8),
+    Instruction::new(set_1_d, 2, 8),
+    Instruction::new(set_1_e, 2, 8),
+    Instruction::new(set_1_h, 2, 8),
+    Instruction::new(set_1_l, 2, 8),
+    Instruction::new(set_1_hlp, 2, 16),
+    Instruction::new(set_1_a, 2, 8),
+    // 0xD_
+    Instruction::new(set_2_b, 2, 8),
+    Instruction::new(set_2_c, 2, 8),
+    Instruction::new(set_2_d, 2, 8),
+    Instruction::new(set_2_e, 2, 8),
+    Instruction::new(set_2_h, 2, 8),
+    Instruction::new(set_2_l, 2, 8),
+    Instruction::new(set_2_hlp, 2, 16),
+    Instruction::new(set_2_a, 2, 8),
+    Instruction::new(set_3_b, 2, 8),
+    Instruction::new(set_3_c, 2, 8),
+    Instruction::new(set_3_d, 2, 8),
+    Instruction::new(set_3_e, 2, 8),
+    Instruction::new(set_3_h, 2, 8),
+    Instruction::new(set_3_l, 2, 8),
+    Instruction::new(set_3_hlp, 2, 16),
+    Instruction::new(set_3_a, 2, 8),
+    // 0xE_
+    Instruction::new(set_4_b, 2, 8),
+    Instruction::new(set_4_c, 2, 8),
+    Instruction::new(set_4_d, 2, 8),
+    Instruction::new(set_4_e, 2, 8),
+    Instruction::new(set_4_h, 2, 8),
+    Instruction::new(set_4_l, 2, 8),
+    Instruction::new(set_4_hlp, 2, 16),
+    Instruction::new(set_4_a, 2, 8),
+    Instruction::new(set_5_b, 2, 8),
+    Instruction::new(set_5_c, 2, 8),
+    Instruction::new(set_5_d, 2, 8),
+    Instruction::new(set_5_e, 2, 8),
+    Instruction::new(set_5_h, 2, 8),
+    Instruction::new(set_5_l, 2, 8),
+    Instruction::new(set_5_hlp, 2, 16),
+    Instruction::new(set_5_a, 2, 8),
+    // 0xF_
+    Instruction::new(set_6_b, 2, 8),
+    Instruction::new(set_6_c, 2, 8),
+    Instruction::new(set_6_d, 2, 8),
+    Instruction::new(set_6_e, 2, 8),
+    Instruction::new(set_6_h, 2, 8),
+    Instruction::new(set_6_l, 2, 8),
+    Instruction::new(set_6_hlp, 2, 16),
+    Instruction::new(set_6_a, 2, 8),
+    Instruction::new(set_7_b, 2, 8),
+    Instruction::new(set_7_c, 2, 8),
+    Instruction::new(set_7_d, 2, 8),
+    Instruction::new(set_7_e, 2, 8),
+    Instruction::new(set_7_h, 2, 8),
+    Instruction::new(set_7_l, 2, 8),
+    Instruction::new(set_7_hlp, 2, 16),
+    Instruction::new(set_7_a, 2, 8),
 ];
-*/
 
 
 /// NOP
 pub fn nop(_cpu: &mut Processor, _instruction: u16) { }
 
-/// INC r8
-pub fn increase_register(cpu: &mut Processor, _instruction: u16, register: Register) {
-    // Increment register by 1 (overflow panics)
-    let register_increment = cpu.read_register(register).checked_add(1).expect("overflow");
-    cpu.write_register(register, register_increment );
+/// Any of the 11 unused opcodes, the CPU hangs until reset
+pub fn illegal(cpu: &mut Processor, _instruction: u16) {
+    cpu.state = State::Locked;
+}
+
+/// HALT
+pub fn halt(cpu: &mut Processor, _instruction: u16) {
+    // Already pending with IME off, HALT exits at once and triggers the HALT bug
+    if !cpu.interrupt_master_enable && cpu.pending_interrupts() != 0 {
+        cpu.halt_bug = true;
+    } else {
+        cpu.state = State::Halted;
+    }
+}
+
+/// STOP
+pub fn stop(cpu: &mut Processor, _instruction: u16) {
+    // Resets DIV like any write to it
+    cpu.write_memory(DIVIDER, 0);
+
+    // Held buttons keep it from stopping
+    if !cpu.joypad_pressed() {
+        cpu.state = State::Stopped;
+    }
+}
 
-    // Calculate half carry bit
-    let h = ((register_increment -1) & 0xF) + (register_increment & 0xF) & 0x10;
+/// value + 1, wrapping like the hardware
+fn increment(cpu: &mut Processor, value: u8) -> u8 {
+    let result = value.wrapping_add(1);
 
     // Set zero flag if zero
-    if register_increment == 0 { cpu.set_flag(Flag::Z); }
+    cpu.assign_flag(Flag::Z, result == 0);
     // Reset subtraction flag
     cpu.reset_flag(Flag::N);
-    // Set Half Carry
-    if h == 0x10 { cpu.set_flag(Flag::H); }
+    // Set Half Carry when the lower nibble overflowed
+    cpu.assign_flag(Flag::H, result & 0x0F == 0x00);
+
+    result
+}
+
+/// value - 1, wrapping like the hardware
+fn decrement(cpu: &mut Processor, value: u8) -> u8 {
+    let result = value.wrapping_sub(1);
+
+    // Set zero flag if zero
+    cpu.assign_flag(Flag::Z, result == 0);
+    // Set subtraction flag
+    cpu.set_flag(Flag::N);
+    // Set Half Carry when the lower nibble borrowed
+    cpu.assign_flag(Flag::H, result & 0x0F == 0x0F);
+
+    result
+}
+
+/// INC r8
+pub fn increase_register(cpu: &mut Processor, _instruction: u16, register: Register) {
+    let register_increment = increment(cpu, cpu.read_register(register));
+    cpu.write_register(register, register_increment);
 }
 
 pub fn inc_a(cpu: &mut Processor, _instruction: u16) { increase_register(cpu, _instruction, Register::A); }
@@ -131,38 +673,17 @@ pub fn inc_l(cpu: &mut Processor, _instruction: u16) { increase_register(cpu, _i
 /// INC [HL]
 pub fn inc_hlp(cpu: &mut Processor, _instruction: u16) {
     // Get position pointed by HL
-    let memory_position = to_u16(cpu.read_register(Register::H), cpu.read_register(Register::L));
+    let memory_position = cpu.read_register16(RegisterPair::HL);
 
-    // Increment register by 1 (overflow panics)
-    let register_increment = cpu.read_memory(memory_position).checked_add(1).expect("overflow");
+    let value = cpu.read_memory(memory_position);
+    let register_increment = increment(cpu, value);
     cpu.write_memory(memory_position, register_increment);
-
-    // Calculate half carry bit
-    let h = ((register_increment -1) & 0xF) + (register_increment & 0xF) & 0x10;
-
-    // Set zero flag if zero
-    if register_increment == 0 { cpu.set_flag(Flag::Z); }
-    // Reset subtraction flag
-    cpu.reset_flag(Flag::N);
-    // Set Half Carry
-    if h == 0x10 { cpu.set_flag(Flag::H); }
 }
 
 /// DEC r8
 pub fn decrease_register(cpu: &mut Processor, _instruction: u16, register: Register) {
-    // Increment register by 1 (overflow panics)
-    let register_decrement = cpu.read_register(register).checked_sub(1).expect("underflow");
-    cpu.write_register(register, register_decrement );
-
-    // Calculate half carry bit
-    let h = register_decrement & 0x0F;
-
-    // Set zero flag if zero
-    if register_decrement == 0 { cpu.set_flag(Flag::Z); }
-    // Set subtraction flag
-    cpu.set_flag(Flag::N);
-    // Set Half Carry
-    if h == 0x00 { cpu.set_flag(Flag::H); }
+    let register_decrement = decrement(cpu, cpu.read_register(register));
+    cpu.write_register(register, register_decrement);
 }
 
 pub fn dec_a(cpu: &mut Processor, _instruction: u16) { decrease_register(cpu, _instruction, Register::A); }
@@ -176,68 +697,42 @@ pub fn dec_l(cpu: &mut Processor, _instruction: u16) { decrease_register(cpu, _i
 /// DEC [HL]
 pub fn dec_hlp(cpu: &mut Processor, _instruction: u16) {
     // Get position pointed by HL
-    let memory_position = to_u16(cpu.read_register(Register::L), cpu.read_register(Register::H));
+    let memory_position = cpu.read_register16(RegisterPair::HL);
 
-    // Decrement register by 1 (overflow panics)
-    let register_decrement = cpu.read_memory(memory_position).checked_sub(1).expect("underflow");
-    cpu.write_memory(memory_position, register_decrement );
-
-    // Calculate half carry bit
-    let h = register_decrement & 0x0F;
-
-    // Set zero flag if zero
-    if register_decrement == 0 { cpu.set_flag(Flag::Z); }
-    // Set subtraction flag
-    cpu.set_flag(Flag::N);
-    // Set Half Carry
-    if h == 0x00 { cpu.set_flag(Flag::H); }
+    let value = cpu.read_memory(memory_position);
+    let register_decrement = decrement(cpu, value);
+    cpu.write_memory(memory_position, register_decrement);
 }
 
 /// INC r16
-pub fn increase_double_register(cpu: &mut Processor, _instruction: u16, register_a: Register, register_b: Register) {
-    // Get position pointed by HL
-    let register_value = to_u16(cpu.read_register(register_a), cpu.read_register(register_b));
+pub fn increase_double_register(cpu: &mut Processor, _instruction: u16, register: RegisterPair) {
+    let register_value = cpu.read_register16(register);
 
-    // Increment register by 1 (overflow panics)
-    let register_increment = register_value.checked_add(1).expect("overflow");
-    let (value_a, value_b) = to_u8(register_increment);
-    cpu.write_register(register_a, value_a);
-    cpu.write_register(register_b, value_b);
+    // Increment register by 1, no flags are affected
+    let register_increment = register_value.wrapping_add(1);
+    cpu.write_register16(register, register_increment);
 }
 
-pub fn inc_bc(cpu: &mut Processor, _instruction: u16) { increase_double_register(cpu, _instruction, Register::B, Register::C); }
-pub fn inc_de(cpu: &mut Processor, _instruction: u16) { increase_double_register(cpu, _instruction, Register::D, Register::E); }
-pub fn inc_hl(cpu: &mut Processor, _instruction: u16) { increase_double_register(cpu, _instruction, Register::H, Register::L); }
+pub fn inc_bc(cpu: &mut Processor, _instruction: u16) { increase_double_register(cpu, _instruction, RegisterPair::BC); }
+pub fn inc_de(cpu: &mut Processor, _instruction: u16) { increase_double_register(cpu, _instruction, RegisterPair::DE); }
+pub fn inc_hl(cpu: &mut Processor, _instruction: u16) { increase_double_register(cpu, _instruction, RegisterPair::HL); }
 
-/// INC SP
-pub fn inc_sp(cpu: &mut Processor, _instruction: u16) {
-    // Increment register by 1 (overflow panics)
-    let register_increment = cpu.stack_pointer.checked_add(1).expect("overflow");
-    cpu.stack_pointer = register_increment;
-}
+pub fn inc_sp(cpu: &mut Processor, _instruction: u16) { increase_double_register(cpu, _instruction, RegisterPair::SP); }
 
 /// DEC r16
-pub fn decrease_double_register(cpu: &mut Processor, _instruction: u16, register_a: Register, register_b: Register) {
-    // Get position pointed by HL
-    let register_value = to_u16(cpu.read_register(register_a), cpu.read_register(register_b));
+pub fn decrease_double_register(cpu: &mut Processor, _instruction: u16, register: RegisterPair) {
+    let register_value = cpu.read_register16(register);
 
-    // Increment register by 1 (overflow panics)
-    let register_increment = register_value.checked_sub(1).expect("overflow");
-    let (value_a, value_b) = to_u8(register_increment);
-    cpu.write_register(register_a, value_a);
-    cpu.write_register(register_b, value_b);
+    // Decrement register by 1, no flags are affected
+    let register_increment = register_value.wrapping_sub(1);
+    cpu.write_register16(register, register_increment);
 }
 
-pub fn dec_bc(cpu: &mut Processor, _instruction: u16) { decrease_double_register(cpu, _instruction, Register::B, Register::C); }
-pub fn dec_de(cpu: &mut Processor, _instruction: u16) { decrease_double_register(cpu, _instruction, Register::D, Register::E); }
-pub fn dec_hl(cpu: &mut Processor, _instruction: u16) { decrease_double_register(cpu, _instruction, Register::H, Register::L); }
+pub fn dec_bc(cpu: &mut Processor, _instruction: u16) { decrease_double_register(cpu, _instruction, RegisterPair::BC); }
+pub fn dec_de(cpu: &mut Processor, _instruction: u16) { decrease_double_register(cpu, _instruction, RegisterPair::DE); }
+pub fn dec_hl(cpu: &mut Processor, _instruction: u16) { decrease_double_register(cpu, _instruction, RegisterPair::HL); }
 
-/// DEC SP
-pub fn dec_sp(cpu: &mut Processor, _instruction: u16) {
-    // Increment register by 1 (overflow panics)
-    let register_increment = cpu.stack_pointer.checked_sub(1).expect("overflow");
-    cpu.stack_pointer = register_increment;
-}
+pub fn dec_sp(cpu: &mut Processor, _instruction: u16) { decrease_double_register(cpu, _instruction, RegisterPair::SP); }
 
 /// LD r8 r8
 pub fn load_register_register(cpu: &mut Processor, _instruction: u16, register_in: Register, register_out: Register) {
@@ -315,26 +810,19 @@ pub fn ld_l_n8(cpu: &mut Processor, instruction: u16) { load_register_value(cpu,
 pub fn ld_a_n8(cpu: &mut Processor, instruction: u16) { load_register_value(cpu, instruction, Register::A); }
 
 /// LD r16 n16
-pub fn load_double_register_value(cpu: &mut Processor, instruction: u16, register_a: Register, register_b: Register) {
+pub fn load_double_register_value(cpu: &mut Processor, instruction: u16, register: RegisterPair) {
     let (value_b, value_a) = to_u8(instruction);
-    cpu.write_register(register_a, value_a);
-    cpu.write_register(register_b, value_b);
+    cpu.write_register16(register, to_u16(value_a, value_b));
 }
 
-pub fn ld_bc_n16(cpu: &mut Processor, instruction: u16) { load_double_register_value(cpu, instruction, Register::B, Register::C); }
-pub fn ld_de_n16(cpu: &mut Processor, instruction: u16) { load_double_register_value(cpu, instruction, Register::D, Register::E); }
-pub fn ld_hl_n16(cpu: &mut Processor, instruction: u16) { load_double_register_value(cpu, instruction, Register::H, Register::L); }
-
-/// LD SP n16
-pub fn ld_sp_n16(cpu: &mut Processor, instruction: u16) {
-    let (value_b, value_a) = to_u8(instruction);
-
-    cpu.stack_pointer = to_u16(value_a, value_b);
-}
+pub fn ld_bc_n16(cpu: &mut Processor, instruction: u16) { load_double_register_value(cpu, instruction, RegisterPair::BC); }
+pub fn ld_de_n16(cpu: &mut Processor, instruction: u16) { load_double_register_value(cpu, instruction, RegisterPair::DE); }
+pub fn ld_hl_n16(cpu: &mut Processor, instruction: u16) { load_double_register_value(cpu, instruction, RegisterPair::HL); }
+pub fn ld_sp_n16(cpu: &mut Processor, instruction: u16) { load_double_register_value(cpu, instruction, RegisterPair::SP); }
 
 /// LD [HL] r8
 pub fn load_hlp_register(cpu: &mut Processor, _instruction: u16, register: Register) {
-    let memory_address = to_u16(cpu.read_register(Register::H), cpu.read_register(Register::L));
+    let memory_address = cpu.read_register16(RegisterPair::HL);
 
     cpu.write_memory(memory_address, cpu.read_register(register));
 }
@@ -349,7 +837,7 @@ pub fn ld_hlp_a(cpu: &mut Processor, _instruction: u16) { load_hlp_register(cpu,
 
 /// LD [HL] n8
 pub fn ld_hlp_n8(cpu: &mut Processor, instruction: u16) {
-    let memory_address = to_u16(cpu.read_register(Register::H), cpu.read_register(Register::L));
+    let memory_address = cpu.read_register16(RegisterPair::HL);
 
     let value = (instruction >> 8) as u8;
 
@@ -358,7 +846,7 @@ pub fn ld_hlp_n8(cpu: &mut Processor, instruction: u16) {
 
 /// LD r8 [HL]
 pub fn load_register_hlp(cpu: &mut Processor, _instruction: u16, register: Register) {
-    let memory_address = to_u16(cpu.read_register(Register::H), cpu.read_register(Register::L));
+    let memory_address = cpu.read_register16(RegisterPair::HL);
     let value = cpu.read_memory(memory_address);
 
     cpu.write_register(register, value);
@@ -373,25 +861,48 @@ pub fn ld_l_hlp(cpu: &mut Processor, _instruction: u16) { load_register_hlp(cpu,
 pub fn ld_a_hlp(cpu: &mut Processor, _instruction: u16) { load_register_hlp(cpu, _instruction, Register::A); }
 
 /// LD [r16] A
-pub fn load_double_registerp_a(cpu: &mut Processor, _instruction: u16, register_a: Register, register_b: Register) {
-    let memory_address = to_u16(cpu.read_register(register_a), cpu.read_register(register_b));
+pub fn load_double_registerp_a(cpu: &mut Processor, _instruction: u16, register: RegisterPair) {
+    let memory_address = cpu.read_register16(register);
     let value = cpu.read_register(Register::A);
 
     cpu.write_memory(memory_address, value);
 }
 
-pub fn ld_bcp_a(cpu: &mut Processor, _instruction: u16) { load_double_registerp_a(cpu, _instruction, Register::B, Register::C); }
-pub fn ld_dep_a(cpu: &mut Processor, _instruction: u16) { load_double_registerp_a(cpu, _instruction, Register::B, Register::C); }
+pub fn ld_bcp_a(cpu: &mut Processor, _instruction: u16) { load_double_registerp_a(cpu, _instruction, RegisterPair::BC); }
+pub fn ld_dep_a(cpu: &mut Processor, _instruction: u16) { load_double_registerp_a(cpu, _instruction, RegisterPair::DE); }
+
+/// LD A [r16]
+pub fn load_a_double_registerp(cpu: &mut Processor, _instruction: u16, register: RegisterPair) {
+    let memory_address = cpu.read_register16(register);
+    let value = cpu.read_memory(memory_address);
+
+    cpu.write_register(Register::A, value);
+}
+
+pub fn ld_a_bcp(cpu: &mut Processor, _instruction: u16) { load_a_double_registerp(cpu, _instruction, RegisterPair::BC); }
+pub fn ld_a_dep(cpu: &mut Processor, _instruction: u16) { load_a_double_registerp(cpu, _instruction, RegisterPair::DE); }
 
 // LD [HL+] A
 pub fn ld_hli_a(cpu: &mut Processor, _instruction: u16) {
-    load_double_registerp_a(cpu, _instruction, Register::H, Register::L);
+    load_double_registerp_a(cpu, _instruction, RegisterPair::HL);
     inc_hl(cpu, _instruction);
 }
 
 // LD [HL-] A
 pub fn ld_hld_a(cpu: &mut Processor, _instruction: u16) {
-    load_double_registerp_a(cpu, _instruction, Register::H, Register::L);
+    load_double_registerp_a(cpu, _instruction, RegisterPair::HL);
+    dec_hl(cpu, _instruction);
+}
+
+// LD A [HL+]
+pub fn ld_a_hli(cpu: &mut Processor, _instruction: u16) {
+    load_a_double_registerp(cpu, _instruction, RegisterPair::HL);
+    inc_hl(cpu, _instruction);
+}
+
+// LD A [HL-]
+pub fn ld_a_hld(cpu: &mut Processor, _instruction: u16) {
+    load_a_double_registerp(cpu, _instruction, RegisterPair::HL);
     dec_hl(cpu, _instruction);
 }
 
@@ -403,6 +914,15 @@ pub fn ld_r16_a(cpu: &mut Processor, instruction: u16) {
     cpu.write_memory(memory_address, cpu.read_register(Register::A));
 }
 
+// LD A [r16]
+pub fn ld_a_r16(cpu: &mut Processor, instruction: u16) {
+    let (address_b, address_a) = to_u8(instruction);
+    let memory_address = to_u16(address_a, address_b);
+    let value = cpu.read_memory(memory_address);
+
+    cpu.write_register(Register::A, value);
+}
+
 // LDH [r8] A
 pub fn ldh_r8_a(cpu: &mut Processor, instruction: u16) {
     let address_a = (instruction >> 8) as u8;
@@ -440,11 +960,7 @@ pub fn ldh_a_c(cpu: &mut Processor, _instruction: u16) {
 pub fn rlca(cpu: &mut Processor, _instruction: u16) {
     let a = cpu.read_register(Register::A);
     let carry = a >> 7;
-    if carry == 0x1 {
-        cpu.set_flag(Flag::C);
-    } else {
-        cpu.reset_flag(Flag::C);
-    }
+    cpu.write_flags(false, false, false, carry == 0x1);
     cpu.write_register(Register::A, (a << 1) | carry);
 }
 
@@ -452,14 +968,843 @@ pub fn rlca(cpu: &mut Processor, _instruction: u16) {
 pub fn rrca(cpu: &mut Processor, _instruction: u16) {
     let a = cpu.read_register(Register::A);
     let carry = a << 7;
-    if carry == 0x80 {
-        cpu.set_flag(Flag::C);
-    } else {
-        cpu.reset_flag(Flag::C);
-    }
+    cpu.write_flags(false, false, false, carry == 0x80);
     cpu.write_register(Register::A, (a >> 1) | carry);
 }
 
+/// RLA
+pub fn rla(cpu: &mut Processor, _instruction: u16) {
+    let a = cpu.read_register(Register::A);
+    let carry_in = cpu.test_flag(Flag::C) as u8;
+    cpu.write_flags(false, false, false, a >> 7 == 0x1);
+    cpu.write_register(Register::A, (a << 1) | carry_in);
+}
+
+/// RRA
+pub fn rra(cpu: &mut Processor, _instruction: u16) {
+    let a = cpu.read_register(Register::A);
+    let carry_in = (cpu.test_flag(Flag::C) as u8) << 7;
+    cpu.write_flags(false, false, false, a & 0x1 == 0x1);
+    cpu.write_register(Register::A, (a >> 1) | carry_in);
+}
+
+/// ALU operation on A with r8
+pub fn alu_register(cpu: &mut Processor, _instruction: u16, register: Register, operation: fn(&mut Processor, u8)) {
+    operation(cpu, cpu.read_register(register));
+}
+
+/// ALU operation on A with [HL]
+pub fn alu_hlp(cpu: &mut Processor, _instruction: u16, operation: fn(&mut Processor, u8)) {
+    let memory_address = cpu.read_register16(RegisterPair::HL);
+    let value = cpu.read_memory(memory_address);
+
+    operation(cpu, value);
+}
+
+/// ALU operation on A with n8
+pub fn alu_n8(cpu: &mut Processor, instruction: u16, operation: fn(&mut Processor, u8)) {
+    let value = (instruction >> 8) as u8;
+    operation(cpu, value);
+}
+
+/// A + value + carry, returns the result without storing it
+fn add_carry_in(cpu: &mut Processor, value: u8, carry_in: u8) -> u8 {
+    let a = cpu.read_register(Register::A);
+    let result = a as u16 + value as u16 + carry_in as u16;
+
+    // Half carry out of bit 3, carry out of bit 7
+    let h = (a & 0xF) + (value & 0xF) + carry_in > 0xF;
+    let c = result > 0xFF;
+
+    cpu.write_flags(result as u8 == 0, false, h, c);
+    result as u8
+}
+
+/// A - value - carry, returns the result without storing it
+fn subtract_carry_in(cpu: &mut Processor, value: u8, carry_in: u8) -> u8 {
+    let a = cpu.read_register(Register::A);
+    let result = (a as i16) - (value as i16) - (carry_in as i16);
+
+    // Borrow from bit 4, borrow from bit 8
+    let h = ((a & 0xF) as i16) - ((value & 0xF) as i16) - (carry_in as i16) < 0;
+    let c = result < 0;
+
+    cpu.write_flags(result as u8 == 0, true, h, c);
+    result as u8
+}
+
+/// ADD A value
+pub fn add(cpu: &mut Processor, value: u8) {
+    let result = add_carry_in(cpu, value, 0);
+    cpu.write_register(Register::A, result);
+}
+
+/// ADC A value
+pub fn add_with_carry(cpu: &mut Processor, value: u8) {
+    let carry_in = cpu.test_flag(Flag::C) as u8;
+    let result = add_carry_in(cpu, value, carry_in);
+    cpu.write_register(Register::A, result);
+}
+
+/// SUB A value
+pub fn subtract(cpu: &mut Processor, value: u8) {
+    let result = subtract_carry_in(cpu, value, 0);
+    cpu.write_register(Register::A, result);
+}
+
+/// SBC A value
+pub fn subtract_with_carry(cpu: &mut Processor, value: u8) {
+    let carry_in = cpu.test_flag(Flag::C) as u8;
+    let result = subtract_carry_in(cpu, value, carry_in);
+    cpu.write_register(Register::A, result);
+}
+
+/// AND A value
+pub fn and(cpu: &mut Processor, value: u8) {
+    let result = cpu.read_register(Register::A) & value;
+    cpu.write_register(Register::A, result);
+    cpu.write_flags(result == 0, false, true, false);
+}
+
+/// XOR A value
+pub fn xor(cpu: &mut Processor, value: u8) {
+    let result = cpu.read_register(Register::A) ^ value;
+    cpu.write_register(Register::A, result);
+    cpu.write_flags(result == 0, false, false, false);
+}
+
+/// OR A value
+pub fn or(cpu: &mut Processor, value: u8) {
+    let result = cpu.read_register(Register::A) | value;
+    cpu.write_register(Register::A, result);
+    cpu.write_flags(result == 0, false, false, false);
+}
+
+/// CP A value (SUB that only keeps the flags)
+pub fn compare(cpu: &mut Processor, value: u8) {
+    subtract_carry_in(cpu, value, 0);
+}
+
+pub fn add_a_b(cpu: &mut Processor, _instruction: u16) { alu_register(cpu, _instruction, Register::B, add); }
+pub fn add_a_c(cpu: &mut Processor, _instruction: u16) { alu_register(cpu, _instruction, Register::C, add); }
+pub fn add_a_d(cpu: &mut Processor, _instruction: u16) { alu_register(cpu, _instruction, Register::D, add); }
+pub fn add_a_e(cpu: &mut Processor, _instruction: u16) { alu_register(cpu, _instruction, Register::E, add); }
+pub fn add_a_h(cpu: &mut Processor, _instruction: u16) { alu_register(cpu, _instruction, Register::H, add); }
+pub fn add_a_l(cpu: &mut Processor, _instruction: u16) { alu_register(cpu, _instruction, Register::L, add); }
+pub fn add_a_hlp(cpu: &mut Processor, _instruction: u16) { alu_hlp(cpu, _instruction, add); }
+pub fn add_a_a(cpu: &mut Processor, _instruction: u16) { alu_register(cpu, _instruction, Register::A, add); }
+pub fn add_a_n8(cpu: &mut Processor, instruction: u16) { alu_n8(cpu, instruction, add); }
+
+pub fn adc_a_b(cpu: &mut Processor, _instruction: u16) { alu_register(cpu, _instruction, Register::B, add_with_carry); }
+pub fn adc_a_c(cpu: &mut Processor, _instruction: u16) { alu_register(cpu, _instruction, Register::C, add_with_carry); }
+pub fn adc_a_d(cpu: &mut Processor, _instruction: u16) { alu_register(cpu, _instruction, Register::D, add_with_carry); }
+pub fn adc_a_e(cpu: &mut Processor, _instruction: u16) { alu_register(cpu, _instruction, Register::E, add_with_carry); }
+pub fn adc_a_h(cpu: &mut Processor, _instruction: u16) { alu_register(cpu, _instruction, Register::H, add_with_carry); }
+pub fn adc_a_l(cpu: &mut Processor, _instruction: u16) { alu_register(cpu, _instruction, Register::L, add_with_carry); }
+pub fn adc_a_hlp(cpu: &mut Processor, _instruction: u16) { alu_hlp(cpu, _instruction, add_with_carry); }
+pub fn adc_a_a(cpu: &mut Processor, _instruction: u16) { alu_register(cpu, _instruction, Register::A, add_with_carry); }
+pub fn adc_a_n8(cpu: &mut Processor, instruction: u16) { alu_n8(cpu, instruction, add_with_carry); }
+
+pub fn sub_a_b(cpu: &mut Processor, _instruction: u16) { alu_register(cpu, _instruction, Register::B, subtract); }
+pub fn sub_a_c(cpu: &mut Processor, _instruction: u16) { alu_register(cpu, _instruction, Register::C, subtract); }
+pub fn sub_a_d(cpu: &mut Processor, _instruction: u16) { alu_register(cpu, _instruction, Register::D, subtract); }
+pub fn sub_a_e(cpu: &mut Processor, _instruction: u16) { alu_register(cpu, _instruction, Register::E, subtract); }
+pub fn sub_a_h(cpu: &mut Processor, _instruction: u16) { alu_register(cpu, _instruction, Register::H, subtract); }
+pub fn sub_a_l(cpu: &mut Processor, _instruction: u16) { alu_register(cpu, _instruction, Register::L, subtract); }
+pub fn sub_a_hlp(cpu: &mut Processor, _instruction: u16) { alu_hlp(cpu, _instruction, subtract); }
+pub fn sub_a_a(cpu: &mut Processor, _instruction: u16) { alu_register(cpu, _instruction, Register::A, subtract); }
+pub fn sub_a_n8(cpu: &mut Processor, instruction: u16) { alu_n8(cpu, instruction, subtract); }
+
+pub fn sbc_a_b(cpu: &mut Processor, _instruction: u16) { alu_register(cpu, _instruction, Register::B, subtract_with_carry); }
+pub fn sbc_a_c(cpu: &mut Processor, _instruction: u16) { alu_register(cpu, _instruction, Register::C, subtract_with_carry); }
+pub fn sbc_a_d(cpu: &mut Processor, _instruction: u16) { alu_register(cpu, _instruction, Register::D, subtract_with_carry); }
+pub fn sbc_a_e(cpu: &mut Processor, _instruction: u16) { alu_register(cpu, _instruction, Register::E, subtract_with_carry); }
+pub fn sbc_a_h(cpu: &mut Processor, _instruction: u16) { alu_register(cpu, _instruction, Register::H, subtract_with_carry); }
+pub fn sbc_a_l(cpu: &mut Processor, _instruction: u16) { alu_register(cpu, _instruction, Register::L, subtract_with_carry); }
+pub fn sbc_a_hlp(cpu: &mut Processor, _instruction: u16) { alu_hlp(cpu, _instruction, subtract_with_carry); }
+pub fn sbc_a_a(cpu: &mut Processor, _instruction: u16) { alu_register(cpu, _instruction, Register::A, subtract_with_carry); }
+pub fn sbc_a_n8(cpu: &mut Processor, instruction: u16) { alu_n8(cpu, instruction, subtract_with_carry); }
+
+pub fn and_a_b(cpu: &mut Processor, _instruction: u16) { alu_register(cpu, _instruction, Register::B, and); }
+pub fn and_a_c(cpu: &mut Processor, _instruction: u16) { alu_register(cpu, _instruction, Register::C, and); }
+pub fn and_a_d(cpu: &mut Processor, _instruction: u16) { alu_register(cpu, _instruction, Register::D, and); }
+pub fn and_a_e(cpu: &mut Processor, _instruction: u16) { alu_register(cpu, _instruction, Register::E, and); }
+pub fn and_a_h(cpu: &mut Processor, _instruction: u16) { alu_register(cpu, _instruction, Register::H, and); }
+pub fn and_a_l(cpu: &mut Processor, _instruction: u16) { alu_register(cpu, _instruction, Register::L, and); }
+pub fn and_a_hlp(cpu: &mut Processor, _instruction: u16) { alu_hlp(cpu, _instruction, and); }
+pub fn and_a_a(cpu: &mut Processor, _instruction: u16) { alu_register(cpu, _instruction, Register::A, and); }
+pub fn and_a_n8(cpu: &mut Processor, instruction: u16) { alu_n8(cpu, instruction, and); }
+
+pub fn xor_a_b(cpu: &mut Processor, _instruction: u16) { alu_register(cpu, _instruction, Register::B, xor); }
+pub fn xor_a_c(cpu: &mut Processor, _instruction: u16) { alu_register(cpu, _instruction, Register::C, xor); }
+pub fn xor_a_d(cpu: &mut Processor, _instruction: u16) { alu_register(cpu, _instruction, Register::D, xor); }
+pub fn xor_a_e(cpu: &mut Processor, _instruction: u16) { alu_register(cpu, _instruction, Register::E, xor); }
+pub fn xor_a_h(cpu: &mut Processor, _instruction: u16) { alu_register(cpu, _instruction, Register::H, xor); }
+pub fn xor_a_l(cpu: &mut Processor, _instruction: u16) { alu_register(cpu, _instruction, Register::L, xor); }
+pub fn xor_a_hlp(cpu: &mut Processor, _instruction: u16) { alu_hlp(cpu, _instruction, xor); }
+pub fn xor_a_a(cpu: &mut Processor, _instruction: u16) { alu_register(cpu, _instruction, Register::A, xor); }
+pub fn xor_a_n8(cpu: &mut Processor, instruction: u16) { alu_n8(cpu, instruction, xor); }
+
+pub fn or_a_b(cpu: &mut Processor, _instruction: u16) { alu_register(cpu, _instruction, Register::B, or); }
+pub fn or_a_c(cpu: &mut Processor, _instruction: u16) { alu_register(cpu, _instruction, Register::C, or); }
+pub fn or_a_d(cpu: &mut Processor, _instruction: u16) { alu_register(cpu, _instruction, Register::D, or); }
+pub fn or_a_e(cpu: &mut Processor, _instruction: u16) { alu_register(cpu, _instruction, Register::E, or); }
+pub fn or_a_h(cpu: &mut Processor, _instruction: u16) { alu_register(cpu, _instruction, Register::H, or); }
+pub fn or_a_l(cpu: &mut Processor, _instruction: u16) { alu_register(cpu, _instruction, Register::L, or); }
+pub fn or_a_hlp(cpu: &mut Processor, _instruction: u16) { alu_hlp(cpu, _instruction, or); }
+pub fn or_a_a(cpu: &mut Processor, _instruction: u16) { alu_register(cpu, _instruction, Register::A, or); }
+pub fn or_a_n8(cpu: &mut Processor, instruction: u16) { alu_n8(cpu, instruction, or); }
+
+pub fn cp_a_b(cpu: &mut Processor, _instruction: u16) { alu_register(cpu, _instruction, Register::B, compare); }
+pub fn cp_a_c(cpu: &mut Processor, _instruction: u16) { alu_register(cpu, _instruction, Register::C, compare); }
+pub fn cp_a_d(cpu: &mut Processor, _instruction: u16) { alu_register(cpu, _instruction, Register::D, compare); }
+pub fn cp_a_e(cpu: &mut Processor, _instruction: u16) { alu_register(cpu, _instruction, Register::E, compare); }
+pub fn cp_a_h(cpu: &mut Processor, _instruction: u16) { alu_register(cpu, _instruction, Register::H, compare); }
+pub fn cp_a_l(cpu: &mut Processor, _instruction: u16) { alu_register(cpu, _instruction, Register::L, compare); }
+pub fn cp_a_hlp(cpu: &mut Processor, _instruction: u16) { alu_hlp(cpu, _instruction, compare); }
+pub fn cp_a_a(cpu: &mut Processor, _instruction: u16) { alu_register(cpu, _instruction, Register::A, compare); }
+pub fn cp_a_n8(cpu: &mut Processor, instruction: u16) { alu_n8(cpu, instruction, compare); }
+
+/// DAA
+pub fn daa(cpu: &mut Processor, _instruction: u16) {
+    let a = cpu.read_register(Register::A);
+    let n = cpu.test_flag(Flag::N);
+    let h = cpu.test_flag(Flag::H);
+    let mut c = cpu.test_flag(Flag::C);
+
+    // Adjust A back into BCD after the last ADD/SUB
+    let mut adjust = 0;
+    if h || (!n && (a & 0xF) > 0x9) { adjust |= 0x06; }
+    if c || (!n && a > 0x99) {
+        adjust |= 0x60;
+        c = true;
+    }
+    let result = if n { a.wrapping_sub(adjust) } else { a.wrapping_add(adjust) };
+
+    cpu.write_register(Register::A, result);
+    cpu.write_flags(result == 0, n, false, c);
+}
+
+/// CPL
+pub fn cpl(cpu: &mut Processor, _instruction: u16) {
+    let a = cpu.read_register(Register::A);
+
+    cpu.write_register(Register::A, !a);
+    cpu.set_flag(Flag::N);
+    cpu.set_flag(Flag::H);
+}
+
+/// SCF
+pub fn scf(cpu: &mut Processor, _instruction: u16) {
+    cpu.reset_flag(Flag::N);
+    cpu.reset_flag(Flag::H);
+    cpu.set_flag(Flag::C);
+}
+
+/// CCF
+pub fn ccf(cpu: &mut Processor, _instruction: u16) {
+    cpu.reset_flag(Flag::N);
+    cpu.reset_flag(Flag::H);
+    cpu.assign_flag(Flag::C, !cpu.test_flag(Flag::C));
+}
+
+/// LD [n16] SP
+pub fn ld_n16_sp(cpu: &mut Processor, instruction: u16) {
+    let (address_b, address_a) = to_u8(instruction);
+    let memory_address = to_u16(address_a, address_b);
+    let (value_a, value_b) = to_u8(cpu.read_register16(RegisterPair::SP));
+
+    cpu.write_memory(memory_address, value_b);
+    cpu.write_memory(memory_address.wrapping_add(1), value_a);
+}
+
+/// LD SP HL
+pub fn ld_sp_hl(cpu: &mut Processor, _instruction: u16) {
+    cpu.write_register16(RegisterPair::SP, cpu.read_register16(RegisterPair::HL));
+}
+
+/// PUSH r16
+pub fn push_double_register(cpu: &mut Processor, _instruction: u16, register: RegisterPair) {
+    let value = cpu.read_register16(register);
+    cpu.push_stack(value);
+}
+
+pub fn push_bc(cpu: &mut Processor, _instruction: u16) { push_double_register(cpu, _instruction, RegisterPair::BC); }
+pub fn push_de(cpu: &mut Processor, _instruction: u16) { push_double_register(cpu, _instruction, RegisterPair::DE); }
+pub fn push_hl(cpu: &mut Processor, _instruction: u16) { push_double_register(cpu, _instruction, RegisterPair::HL); }
+pub fn push_af(cpu: &mut Processor, _instruction: u16) { push_double_register(cpu, _instruction, RegisterPair::AF); }
+
+/// POP r16
+pub fn pop_double_register(cpu: &mut Processor, _instruction: u16, register: RegisterPair) {
+    let value = cpu.pop_stack();
+    cpu.write_register16(register, value);
+}
+
+pub fn pop_bc(cpu: &mut Processor, _instruction: u16) { pop_double_register(cpu, _instruction, RegisterPair::BC); }
+pub fn pop_de(cpu: &mut Processor, _instruction: u16) { pop_double_register(cpu, _instruction, RegisterPair::DE); }
+pub fn pop_hl(cpu: &mut Processor, _instruction: u16) { pop_double_register(cpu, _instruction, RegisterPair::HL); }
+pub fn pop_af(cpu: &mut Processor, _instruction: u16) { pop_double_register(cpu, _instruction, RegisterPair::AF); }
+
+/// ADD HL value
+fn add_hl(cpu: &mut Processor, value: u16) {
+    let hl = cpu.read_register16(RegisterPair::HL);
+    let (result, c) = hl.overflowing_add(value);
+
+    // Half carry out of bit 11, zero flag is kept
+    let h = (hl & 0x0FFF) + (value & 0x0FFF) > 0x0FFF;
+
+    cpu.write_register16(RegisterPair::HL, result);
+    cpu.reset_flag(Flag::N);
+    cpu.assign_flag(Flag::H, h);
+    cpu.assign_flag(Flag::C, c);
+}
+
+/// ADD HL r16
+pub fn add_hl_double_register(cpu: &mut Processor, _instruction: u16, register: RegisterPair) {
+    let value = cpu.read_register16(register);
+    add_hl(cpu, value);
+}
+
+pub fn add_hl_bc(cpu: &mut Processor, _instruction: u16) { add_hl_double_register(cpu, _instruction, RegisterPair::BC); }
+pub fn add_hl_de(cpu: &mut Processor, _instruction: u16) { add_hl_double_register(cpu, _instruction, RegisterPair::DE); }
+pub fn add_hl_hl(cpu: &mut Processor, _instruction: u16) { add_hl_double_register(cpu, _instruction, RegisterPair::HL); }
+pub fn add_hl_sp(cpu: &mut Processor, _instruction: u16) { add_hl_double_register(cpu, _instruction, RegisterPair::SP); }
+
+/// SP + e8, flags come from the unsigned add on the low byte
+fn stack_pointer_offset(cpu: &mut Processor, instruction: u16) -> u16 {
+    let offset = (instruction >> 8) as u8;
+    let sp = cpu.read_register16(RegisterPair::SP);
+
+    let h = (sp & 0x0F) + (offset as u16 & 0x0F) > 0x0F;
+    let c = (sp & 0xFF) + offset as u16 > 0xFF;
+    cpu.write_flags(false, false, h, c);
+
+    sp.wrapping_add(offset as i8 as u16)
+}
+
+/// ADD SP e8
+pub fn add_sp_e8(cpu: &mut Processor, instruction: u16) {
+    let value = stack_pointer_offset(cpu, instruction);
+    cpu.write_register16(RegisterPair::SP, value);
+}
+
+/// LD HL SP+e8
+pub fn ld_hl_sp_e8(cpu: &mut Processor, instruction: u16) {
+    let value = stack_pointer_offset(cpu, instruction);
+    cpu.write_register16(RegisterPair::HL, value);
+}
+
+/// JP n16 if condition
+pub fn jump(cpu: &mut Processor, instruction: u16, condition: bool) {
+    if !condition { return; }
+
+    let (address_b, address_a) = to_u8(instruction);
+    cpu.program_counter = to_u16(address_a, address_b);
+    cpu.branch_taken = true;
+}
+
+pub fn jp_n16(cpu: &mut Processor, instruction: u16) { jump(cpu, instruction, true); }
+pub fn jp_nz_n16(cpu: &mut Processor, instruction: u16) { jump(cpu, instruction, !cpu.test_flag(Flag::Z)); }
+pub fn jp_z_n16(cpu: &mut Processor, instruction: u16) { jump(cpu, instruction, cpu.test_flag(Flag::Z)); }
+pub fn jp_nc_n16(cpu: &mut Processor, instruction: u16) { jump(cpu, instruction, !cpu.test_flag(Flag::C)); }
+pub fn jp_c_n16(cpu: &mut Processor, instruction: u16) { jump(cpu, instruction, cpu.test_flag(Flag::C)); }
+
+/// JP HL
+pub fn jp_hl(cpu: &mut Processor, _instruction: u16) {
+    cpu.program_counter = cpu.read_register16(RegisterPair::HL);
+}
+
+/// JR e8 if condition, relative to the next instruction
+pub fn jump_relative(cpu: &mut Processor, instruction: u16, condition: bool) {
+    if !condition { return; }
+
+    let offset = (instruction >> 8) as u8 as i8;
+    cpu.program_counter = cpu.program_counter.wrapping_add(offset as u16);
+    cpu.branch_taken = true;
+}
+
+pub fn jr_e8(cpu: &mut Processor, instruction: u16) { jump_relative(cpu, instruction, true); }
+pub fn jr_nz_e8(cpu: &mut Processor, instruction: u16) { jump_relative(cpu, instruction, !cpu.test_flag(Flag::Z)); }
+pub fn jr_z_e8(cpu: &mut Processor, instruction: u16) { jump_relative(cpu, instruction, cpu.test_flag(Flag::Z)); }
+pub fn jr_nc_e8(cpu: &mut Processor, instruction: u16) { jump_relative(cpu, instruction, !cpu.test_flag(Flag::C)); }
+pub fn jr_c_e8(cpu: &mut Processor, instruction: u16) { jump_relative(cpu, instruction, cpu.test_flag(Flag::C)); }
+
+/// CALL n16 if condition
+pub fn call(cpu: &mut Processor, instruction: u16, condition: bool) {
+    if !condition { return; }
+
+    cpu.push_stack(cpu.program_counter);
+    jump(cpu, instruction, true);
+}
+
+pub fn call_n16(cpu: &mut Processor, instruction: u16) { call(cpu, instruction, true); }
+pub fn call_nz_n16(cpu: &mut Processor, instruction: u16) { call(cpu, instruction, !cpu.test_flag(Flag::Z)); }
+pub fn call_z_n16(cpu: &mut Processor, instruction: u16) { call(cpu, instruction, cpu.test_flag(Flag::Z)); }
+pub fn call_nc_n16(cpu: &mut Processor, instruction: u16) { call(cpu, instruction, !cpu.test_flag(Flag::C)); }
+pub fn call_c_n16(cpu: &mut Processor, instruction: u16) { call(cpu, instruction, cpu.test_flag(Flag::C)); }
+
+/// RET if condition
+pub fn return_from_call(cpu: &mut Processor, _instruction: u16, condition: bool) {
+    if !condition { return; }
+
+    cpu.program_counter = cpu.pop_stack();
+    cpu.branch_taken = true;
+}
+
+pub fn ret(cpu: &mut Processor, _instruction: u16) { return_from_call(cpu, _instruction, true); }
+pub fn ret_nz(cpu: &mut Processor, _instruction: u16) { return_from_call(cpu, _instruction, !cpu.test_flag(Flag::Z)); }
+pub fn ret_z(cpu: &mut Processor, _instruction: u16) { return_from_call(cpu, _instruction, cpu.test_flag(Flag::Z)); }
+pub fn ret_nc(cpu: &mut Processor, _instruction: u16) { return_from_call(cpu, _instruction, !cpu.test_flag(Flag::C)); }
+pub fn ret_c(cpu: &mut Processor, _instruction: u16) { return_from_call(cpu, _instruction, cpu.test_flag(Flag::C)); }
+
+/// RETI
+pub fn reti(cpu: &mut Processor, _instruction: u16) {
+    return_from_call(cpu, _instruction, true);
+    // Unlike EI there is no delay
+    cpu.interrupt_master_enable = true;
+}
+
+/// DI
+pub fn di(cpu: &mut Processor, _instruction: u16) {
+    cpu.interrupt_master_enable = false;
+    cpu.interrupt_enable_scheduled = false;
+}
+
+/// EI
+pub fn ei(cpu: &mut Processor, _instruction: u16) {
+    cpu.interrupt_enable_scheduled = true;
+}
+
+/// RST vec
+pub fn restart(cpu: &mut Processor, _instruction: u16, vector: u16) {
+    cpu.push_stack(cpu.program_counter);
+    cpu.program_counter = vector;
+}
+
+pub fn rst_00(cpu: &mut Processor, _instruction: u16) { restart(cpu, _instruction, 0x00); }
+pub fn rst_08(cpu: &mut Processor, _instruction: u16) { restart(cpu, _instruction, 0x08); }
+pub fn rst_10(cpu: &mut Processor, _instruction: u16) { restart(cpu, _instruction, 0x10); }
+pub fn rst_18(cpu: &mut Processor, _instruction: u16) { restart(cpu, _instruction, 0x18); }
+pub fn rst_20(cpu: &mut Processor, _instruction: u16) { restart(cpu, _instruction, 0x20); }
+pub fn rst_28(cpu: &mut Processor, _instruction: u16) { restart(cpu, _instruction, 0x28); }
+pub fn rst_30(cpu: &mut Processor, _instruction: u16) { restart(cpu, _instruction, 0x30); }
+pub fn rst_38(cpu: &mut Processor, _instruction: u16) { restart(cpu, _instruction, 0x38); }
+
+/// PREFIX CB
+/// The processor decodes 0xCB straight from CB_INSTRUCTIONS for the exact cycles,
+/// this keeps INSTRUCTIONS[0xCB] right for anyone indexing the table directly
+pub fn prefix_cb(cpu: &mut Processor, instruction: u16) {
+    let opcode = (instruction >> 8) as usize;
+    (CB_INSTRUCTIONS[opcode].operation)(cpu, instruction);
+}
+
+/// CB operation on r8
+pub fn cb_register(cpu: &mut Processor, _instruction: u16, register: Register, operation: fn(&mut Processor, u8) -> u8) {
+    let value = operation(cpu, cpu.read_register(register));
+    cpu.write_register(register, value);
+}
+
+/// CB operation on [HL]
+pub fn cb_hlp(cpu: &mut Processor, _instruction: u16, operation: fn(&mut Processor, u8) -> u8) {
+    let memory_address = cpu.read_register16(RegisterPair::HL);
+
+    let value = cpu.read_memory(memory_address);
+    let value = operation(cpu, value);
+    cpu.write_memory(memory_address, value);
+}
+
+/// RLC
+pub fn rotate_left_circular(cpu: &mut Processor, value: u8) -> u8 {
+    let result = value.rotate_left(1);
+    cpu.write_flags(result == 0, false, false, value & 0x80 != 0);
+    result
+}
+
+/// RRC
+pub fn rotate_right_circular(cpu: &mut Processor, value: u8) -> u8 {
+    let result = value.rotate_right(1);
+    cpu.write_flags(result == 0, false, false, value & 0x01 != 0);
+    result
+}
+
+/// RL
+pub fn rotate_left(cpu: &mut Processor, value: u8) -> u8 {
+    let carry_in = cpu.test_flag(Flag::C) as u8;
+    let result = (value << 1) | carry_in;
+    cpu.write_flags(result == 0, false, false, value & 0x80 != 0);
+    result
+}
+
+/// RR
+pub fn rotate_right(cpu: &mut Processor, value: u8) -> u8 {
+    let carry_in = (cpu.test_flag(Flag::C) as u8) << 7;
+    let result = (value >> 1) | carry_in;
+    cpu.write_flags(result == 0, false, false, value & 0x01 != 0);
+    result
+}
+
+/// SLA
+pub fn shift_left_arithmetic(cpu: &mut Processor, value: u8) -> u8 {
+    let result = value << 1;
+    cpu.write_flags(result == 0, false, false, value & 0x80 != 0);
+    result
+}
+
+/// SRA (bit 7 is kept)
+pub fn shift_right_arithmetic(cpu: &mut Processor, value: u8) -> u8 {
+    let result = (value >> 1) | (value & 0x80);
+    cpu.write_flags(result == 0, false, false, value & 0x01 != 0);
+    result
+}
+
+/// SWAP
+pub fn swap_nibbles(cpu: &mut Processor, value: u8) -> u8 {
+    let result = value.rotate_left(4);
+    cpu.write_flags(result == 0, false, false, false);
+    result
+}
+
+/// SRL
+pub fn shift_right_logical(cpu: &mut Processor, value: u8) -> u8 {
+    let result = value >> 1;
+    cpu.write_flags(result == 0, false, false, value & 0x01 != 0);
+    result
+}
+
+pub fn rlc_b(cpu: &mut Processor, _instruction: u16) { cb_register(cpu, _instruction, Register::B, rotate_left_circular); }
+pub fn rlc_c(cpu: &mut Processor, _instruction: u16) { cb_register(cpu, _instruction, Register::C, rotate_left_circular); }
+pub fn rlc_d(cpu: &mut Processor, _instruction: u16) { cb_register(cpu, _instruction, Register::D, rotate_left_circular); }
+pub fn rlc_e(cpu: &mut Processor, _instruction: u16) { cb_register(cpu, _instruction, Register::E, rotate_left_circular); }
+pub fn rlc_h(cpu: &mut Processor, _instruction: u16) { cb_register(cpu, _instruction, Register::H, rotate_left_circular); }
+pub fn rlc_l(cpu: &mut Processor, _instruction: u16) { cb_register(cpu, _instruction, Register::L, rotate_left_circular); }
+pub fn rlc_hlp(cpu: &mut Processor, _instruction: u16) { cb_hlp(cpu, _instruction, rotate_left_circular); }
+pub fn rlc_a(cpu: &mut Processor, _instruction: u16) { cb_register(cpu, _instruction, Register::A, rotate_left_circular); }
+
+pub fn rrc_b(cpu: &mut Processor, _instruction: u16) { cb_register(cpu, _instruction, Register::B, rotate_right_circular); }
+pub fn rrc_c(cpu: &mut Processor, _instruction: u16) { cb_register(cpu, _instruction, Register::C, rotate_right_circular); }
+pub fn rrc_d(cpu: &mut Processor, _instruction: u16) { cb_register(cpu, _instruction, Register::D, rotate_right_circular); }
+pub fn rrc_e(cpu: &mut Processor, _instruction: u16) { cb_register(cpu, _instruction, Register::E, rotate_right_circular); }
+pub fn rrc_h(cpu: &mut Processor, _instruction: u16) { cb_register(cpu, _instruction, Register::H, rotate_right_circular); }
+pub fn rrc_l(cpu: &mut Processor, _instruction: u16) { cb_register(cpu, _instruction, Register::L, rotate_right_circular); }
+pub fn rrc_hlp(cpu: &mut Processor, _instruction: u16) { cb_hlp(cpu, _instruction, rotate_right_circular); }
+pub fn rrc_a(cpu: &mut Processor, _instruction: u16) { cb_register(cpu, _instruction, Register::A, rotate_right_circular); }
+
+pub fn rl_b(cpu: &mut Processor, _instruction: u16) { cb_register(cpu, _instruction, Register::B, rotate_left); }
+pub fn rl_c(cpu: &mut Processor, _instruction: u16) { cb_register(cpu, _instruction, Register::C, rotate_left); }
+pub fn rl_d(cpu: &mut Processor, _instruction: u16) { cb_register(cpu, _instruction, Register::D, rotate_left); }
+pub fn rl_e(cpu: &mut Processor, _instruction: u16) { cb_register(cpu, _instruction, Register::E, rotate_left); }
+pub fn rl_h(cpu: &mut Processor, _instruction: u16) { cb_register(cpu, _instruction, Register::H, rotate_left); }
+pub fn rl_l(cpu: &mut Processor, _instruction: u16) { cb_register(cpu, _instruction, Register::L, rotate_left); }
+pub fn rl_hlp(cpu: &mut Processor, _instruction: u16) { cb_hlp(cpu, _instruction, rotate_left); }
+pub fn rl_a(cpu: &mut Processor, _instruction: u16) { cb_register(cpu, _instruction, Register::A, rotate_left); }
+
+pub fn rr_b(cpu: &mut Processor, _instruction: u16) { cb_register(cpu, _instruction, Register::B, rotate_right); }
+pub fn rr_c(cpu: &mut Processor, _instruction: u16) { cb_register(cpu, _instruction, Register::C, rotate_right); }
+pub fn rr_d(cpu: &mut Processor, _instruction: u16) { cb_register(cpu, _instruction, Register::D, rotate_right); }
+pub fn rr_e(cpu: &mut Processor, _instruction: u16) { cb_register(cpu, _instruction, Register::E, rotate_right); }
+pub fn rr_h(cpu: &mut Processor, _instruction: u16) { cb_register(cpu, _instruction, Register::H, rotate_right); }
+pub fn rr_l(cpu: &mut Processor, _instruction: u16) { cb_register(cpu, _instruction, Register::L, rotate_right); }
+pub fn rr_hlp(cpu: &mut Processor, _instruction: u16) { cb_hlp(cpu, _instruction, rotate_right); }
+pub fn rr_a(cpu: &mut Processor, _instruction: u16) { cb_register(cpu, _instruction, Register::A, rotate_right); }
+
+pub fn sla_b(cpu: &mut Processor, _instruction: u16) { cb_register(cpu, _instruction, Register::B, shift_left_arithmetic); }
+pub fn sla_c(cpu: &mut Processor, _instruction: u16) { cb_register(cpu, _instruction, Register::C, shift_left_arithmetic); }
+pub fn sla_d(cpu: &mut Processor, _instruction: u16) { cb_register(cpu, _instruction, Register::D, shift_left_arithmetic); }
+pub fn sla_e(cpu: &mut Processor, _instruction: u16) { cb_register(cpu, _instruction, Register::E, shift_left_arithmetic); }
+pub fn sla_h(cpu: &mut Processor, _instruction: u16) { cb_register(cpu, _instruction, Register::H, shift_left_arithmetic); }
+pub fn sla_l(cpu: &mut Processor, _instruction: u16) { cb_register(cpu, _instruction, Register::L, shift_left_arithmetic); }
+pub fn sla_hlp(cpu: &mut Processor, _instruction: u16) { cb_hlp(cpu, _instruction, shift_left_arithmetic); }
+pub fn sla_a(cpu: &mut Processor, _instruction: u16) { cb_register(cpu, _instruction, Register::A, shift_left_arithmetic); }
+
+pub fn sra_b(cpu: &mut Processor, _instruction: u16) { cb_register(cpu, _instruction, Register::B, shift_right_arithmetic); }
+pub fn sra_c(cpu: &mut Processor, _instruction: u16) { cb_register(cpu, _instruction, Register::C, shift_right_arithmetic); }
+pub fn sra_d(cpu: &mut Processor, _instruction: u16) { cb_register(cpu, _instruction, Register::D, shift_right_arithmetic); }
+pub fn sra_e(cpu: &mut Processor, _instruction: u16) { cb_register(cpu, _instruction, Register::E, shift_right_arithmetic); }
+pub fn sra_h(cpu: &mut Processor, _instruction: u16) { cb_register(cpu, _instruction, Register::H, shift_right_arithmetic); }
+pub fn sra_l(cpu: &mut Processor, _instruction: u16) { cb_register(cpu, _instruction, Register::L, shift_right_arithmetic); }
+pub fn sra_hlp(cpu: &mut Processor, _instruction: u16) { cb_hlp(cpu, _instruction, shift_right_arithmetic); }
+pub fn sra_a(cpu: &mut Processor, _instruction: u16) { cb_register(cpu, _instruction, Register::A, shift_right_arithmetic); }
+
+pub fn swap_b(cpu: &mut Processor, _instruction: u16) { cb_register(cpu, _instruction, Register::B, swap_nibbles); }
+pub fn swap_c(cpu: &mut Processor, _instruction: u16) { cb_register(cpu, _instruction, Register::C, swap_nibbles); }
+pub fn swap_d(cpu: &mut Processor, _instruction: u16) { cb_register(cpu, _instruction, Register::D, swap_nibbles); }
+pub fn swap_e(cpu: &mut Processor, _instruction: u16) { cb_register(cpu, _instruction, Register::E, swap_nibbles); }
+pub fn swap_h(cpu: &mut Processor, _instruction: u16) { cb_register(cpu, _instruction, Register::H, swap_nibbles); }
+pub fn swap_l(cpu: &mut Processor, _instruction: u16) { cb_register(cpu, _instruction, Register::L, swap_nibbles); }
+pub fn swap_hlp(cpu: &mut Processor, _instruction: u16) { cb_hlp(cpu, _instruction, swap_nibbles); }
+pub fn swap_a(cpu: &mut Processor, _instruction: u16) { cb_register(cpu, _instruction, Register::A, swap_nibbles); }
+
+pub fn srl_b(cpu: &mut Processor, _instruction: u16) { cb_register(cpu, _instruction, Register::B, shift_right_logical); }
+pub fn srl_c(cpu: &mut Processor, _instruction: u16) { cb_register(cpu, _instruction, Register::C, shift_right_logical); }
+pub fn srl_d(cpu: &mut Processor, _instruction: u16) { cb_register(cpu, _instruction, Register::D, shift_right_logical); }
+pub fn srl_e(cpu: &mut Processor, _instruction: u16) { cb_register(cpu, _instruction, Register::E, shift_right_logical); }
+pub fn srl_h(cpu: &mut Processor, _instruction: u16) { cb_register(cpu, _instruction, Register::H, shift_right_logical); }
+pub fn srl_l(cpu: &mut Processor, _instruction: u16) { cb_register(cpu, _instruction, Register::L, shift_right_logical); }
+pub fn srl_hlp(cpu: &mut Processor, _instruction: u16) { cb_hlp(cpu, _instruction, shift_right_logical); }
+pub fn srl_a(cpu: &mut Processor, _instruction: u16) { cb_register(cpu, _instruction, Register::A, shift_right_logical); }
+
+/// BIT u3 value (carry is preserved)
+fn test_bit(cpu: &mut Processor, bit: u8, value: u8) {
+    cpu.assign_flag(Flag::Z, value & (1 << bit) == 0);
+    cpu.reset_flag(Flag::N);
+    cpu.set_flag(Flag::H);
+}
+
+/// BIT u3 r8
+pub fn test_bit_register(cpu: &mut Processor, _instruction: u16, bit: u8, register: Register) {
+    test_bit(cpu, bit, cpu.read_register(register));
+}
+
+/// BIT u3 [HL]
+pub fn test_bit_hlp(cpu: &mut Processor, _instruction: u16, bit: u8) {
+    let memory_address = cpu.read_register16(RegisterPair::HL);
+    let value = cpu.read_memory(memory_address);
+
+    test_bit(cpu, bit, value);
+}
+
+pub fn bit_0_b(cpu: &mut Processor, _instruction: u16) { test_bit_register(cpu, _instruction, 0, Register::B); }
+pub fn bit_0_c(cpu: &mut Processor, _instruction: u16) { test_bit_register(cpu, _instruction, 0, Register::C); }
+pub fn bit_0_d(cpu: &mut Processor, _instruction: u16) { test_bit_register(cpu, _instruction, 0, Register::D); }
+pub fn bit_0_e(cpu: &mut Processor, _instruction: u16) { test_bit_register(cpu, _instruction, 0, Register::E); }
+pub fn bit_0_h(cpu: &mut Processor, _instruction: u16) { test_bit_register(cpu, _instruction, 0, Register::H); }
+pub fn bit_0_l(cpu: &mut Processor, _instruction: u16) { test_bit_register(cpu, _instruction, 0, Register::L); }
+pub fn bit_0_hlp(cpu: &mut Processor, _instruction: u16) { test_bit_hlp(cpu, _instruction, 0); }
+pub fn bit_0_a(cpu: &mut Processor, _instruction: u16) { test_bit_register(cpu, _instruction, 0, Register::A); }
+
+pub fn bit_1_b(cpu: &mut Processor, _instruction: u16) { test_bit_register(cpu, _instruction, 1, Register::B); }
+pub fn bit_1_c(cpu: &mut Processor, _instruction: u16) { test_bit_register(cpu, _instruction, 1, Register::C); }
+pub fn bit_1_d(cpu: &mut Processor, _instruction: u16) { test_bit_register(cpu, _instruction, 1, Register::D); }
+pub fn bit_1_e(cpu: &mut Processor, _instruction: u16) { test_bit_register(cpu, _instruction, 1, Register::E); }
+pub fn bit_1_h(cpu: &mut Processor, _instruction: u16) { test_bit_register(cpu, _instruction, 1, Register::H); }
+pub fn bit_1_l(cpu: &mut Processor, _instruction: u16) { test_bit_register(cpu, _instruction, 1, Register::L); }
+pub fn bit_1_hlp(cpu: &mut Processor, _instruction: u16) { test_bit_hlp(cpu, _instruction, 1); }
+pub fn bit_1_a(cpu: &mut Processor, _instruction: u16) { test_bit_register(cpu, _instruction, 1, Register::A); }
+
+pub fn bit_2_b(cpu: &mut Processor, _instruction: u16) { test_bit_register(cpu, _instruction, 2, Register::B); }
+pub fn bit_2_c(cpu: &mut Processor, _instruction: u16) { test_bit_register(cpu, _instruction, 2, Register::C); }
+pub fn bit_2_d(cpu: &mut Processor, _instruction: u16) { test_bit_register(cpu, _instruction, 2, Register::D); }
+pub fn bit_2_e(cpu: &mut Processor, _instruction: u16) { test_bit_register(cpu, _instruction, 2, Register::E); }
+pub fn bit_2_h(cpu: &mut Processor, _instruction: u16) { test_bit_register(cpu, _instruction, 2, Register::H); }
+pub fn bit_2_l(cpu: &mut Processor, _instruction: u16) { test_bit_register(cpu, _instruction, 2, Register::L); }
+pub fn bit_2_hlp(cpu: &mut Processor, _instruction: u16) { test_bit_hlp(cpu, _instruction, 2); }
+pub fn bit_2_a(cpu: &mut Processor, _instruction: u16) { test_bit_register(cpu, _instruction, 2, Register::A); }
+
+pub fn bit_3_b(cpu: &mut Processor, _instruction: u16) { test_bit_register(cpu, _instruction, 3, Register::B); }
+pub fn bit_3_c(cpu: &mut Processor, _instruction: u16) { test_bit_register(cpu, _instruction, 3, Register::C); }
+pub fn bit_3_d(cpu: &mut Processor, _instruction: u16) { test_bit_register(cpu, _instruction, 3, Register::D); }
+pub fn bit_3_e(cpu: &mut Processor, _instruction: u16) { test_bit_register(cpu, _instruction, 3, Register::E); }
+pub fn bit_3_h(cpu: &mut Processor, _instruction: u16) { test_bit_register(cpu, _instruction, 3, Register::H); }
+pub fn bit_3_l(cpu: &mut Processor, _instruction: u16) { test_bit_register(cpu, _instruction, 3, Register::L); }
+pub fn bit_3_hlp(cpu: &mut Processor, _instruction: u16) { test_bit_hlp(cpu, _instruction, 3); }
+pub fn bit_3_a(cpu: &mut Processor, _instruction: u16) { test_bit_register(cpu, _instruction, 3, Register::A); }
+
+pub fn bit_4_b(cpu: &mut Processor, _instruction: u16) { test_bit_register(cpu, _instruction, 4, Register::B); }
+pub fn bit_4_c(cpu: &mut Processor, _instruction: u16) { test_bit_register(cpu, _instruction, 4, Register::C); }
+pub fn bit_4_d(cpu: &mut Processor, _instruction: u16) { test_bit_register(cpu, _instruction, 4, Register::D); }
+pub fn bit_4_e(cpu: &mut Processor, _instruction: u16) { test_bit_register(cpu, _instruction, 4, Register::E); }
+pub fn bit_4_h(cpu: &mut Processor, _instruction: u16) { test_bit_register(cpu, _instruction, 4, Register::H); }
+pub fn bit_4_l(cpu: &mut Processor, _instruction: u16) { test_bit_register(cpu, _instruction, 4, Register::L); }
+pub fn bit_4_hlp(cpu: &mut Processor, _instruction: u16) { test_bit_hlp(cpu, _instruction, 4); }
+pub fn bit_4_a(cpu: &mut Processor, _instruction: u16) { test_bit_register(cpu, _instruction, 4, Register::A); }
+
+pub fn bit_5_b(cpu: &mut Processor, _instruction: u16) { test_bit_register(cpu, _instruction, 5, Register::B); }
+pub fn bit_5_c(cpu: &mut Processor, _instruction: u16) { test_bit_register(cpu, _instruction, 5, Register::C); }
+pub fn bit_5_d(cpu: &mut Processor, _instruction: u16) { test_bit_register(cpu, _instruction, 5, Register::D); }
+pub fn bit_5_e(cpu: &mut Processor, _instruction: u16) { test_bit_register(cpu, _instruction, 5, Register::E); }
+pub fn bit_5_h(cpu: &mut Processor, _instruction: u16) { test_bit_register(cpu, _instruction, 5, Register::H); }
+pub fn bit_5_l(cpu: &mut Processor, _instruction: u16) { test_bit_register(cpu, _instruction, 5, Register::L); }
+pub fn bit_5_hlp(cpu: &mut Processor, _instruction: u16) { test_bit_hlp(cpu, _instruction, 5); }
+pub fn bit_5_a(cpu: &mut Processor, _instruction: u16) { test_bit_register(cpu, _instruction, 5, Register::A); }
+
+pub fn bit_6_b(cpu: &mut Processor, _instruction: u16) { test_bit_register(cpu, _instruction, 6, Register::B); }
+pub fn bit_6_c(cpu: &mut Processor, _instruction: u16) { test_bit_register(cpu, _instruction, 6, Register::C); }
+pub fn bit_6_d(cpu: &mut Processor, _instruction: u16) { test_bit_register(cpu, _instruction, 6, Register::D); }
+pub fn bit_6_e(cpu: &mut Processor, _instruction: u16) { test_bit_register(cpu, _instruction, 6, Register::E); }
+pub fn bit_6_h(cpu: &mut Processor, _instruction: u16) { test_bit_register(cpu, _instruction, 6, Register::H); }
+pub fn bit_6_l(cpu: &mut Processor, _instruction: u16) { test_bit_register(cpu, _instruction, 6, Register::L); }
+pub fn bit_6_hlp(cpu: &mut Processor, _instruction: u16) { test_bit_hlp(cpu, _instruction, 6); }
+pub fn bit_6_a(cpu: &mut Processor, _instruction: u16) { test_bit_register(cpu, _instruction, 6, Register::A); }
+
+pub fn bit_7_b(cpu: &mut Processor, _instruction: u16) { test_bit_register(cpu, _instruction, 7, Register::B); }
+pub fn bit_7_c(cpu: &mut Processor, _instruction: u16) { test_bit_register(cpu, _instruction, 7, Register::C); }
+pub fn bit_7_d(cpu: &mut Processor, _instruction: u16) { test_bit_register(cpu, _instruction, 7, Register::D); }
+pub fn bit_7_e(cpu: &mut Processor, _instruction: u16) { test_bit_register(cpu, _instruction, 7, Register::E); }
+pub fn bit_7_h(cpu: &mut Processor, _instruction: u16) { test_bit_register(cpu, _instruction, 7, Register::H); }
+pub fn bit_7_l(cpu: &mut Processor, _instruction: u16) { test_bit_register(cpu, _instruction, 7, Register::L); }
+pub fn bit_7_hlp(cpu: &mut Processor, _instruction: u16) { test_bit_hlp(cpu, _instruction, 7); }
+pub fn bit_7_a(cpu: &mut Processor, _instruction: u16) { test_bit_register(cpu, _instruction, 7, Register::A); }
+
+/// RES u3 r8
+pub fn reset_bit_register(cpu: &mut Processor, _instruction: u16, bit: u8, register: Register) {
+    cpu.write_register(register, cpu.read_register(register) & !(1 << bit));
+}
+
+/// RES u3 [HL]
+pub fn reset_bit_hlp(cpu: &mut Processor, _instruction: u16, bit: u8) {
+    let memory_address = cpu.read_register16(RegisterPair::HL);
+    let value = cpu.read_memory(memory_address);
+
+    cpu.write_memory(memory_address, value & !(1 << bit));
+}
+
+pub fn res_0_b(cpu: &mut Processor, _instruction: u16) { reset_bit_register(cpu, _instruction, 0, Register::B); }
+pub fn res_0_c(cpu: &mut Processor, _instruction: u16) { reset_bit_register(cpu, _instruction, 0, Register::C); }
+pub fn res_0_d(cpu: &mut Processor, _instruction: u16) { reset_bit_register(cpu, _instruction, 0, Register::D); }
+pub fn res_0_e(cpu: &mut Processor, _instruction: u16) { reset_bit_register(cpu, _instruction, 0, Register::E); }
+pub fn res_0_h(cpu: &mut Processor, _instruction: u16) { reset_bit_register(cpu, _instruction, 0, Register::H); }
+pub fn res_0_l(cpu: &mut Processor, _instruction: u16) { reset_bit_register(cpu, _instruction, 0, Register::L); }
+pub fn res_0_hlp(cpu: &mut Processor, _instruction: u16) { reset_bit_hlp(cpu, _instruction, 0); }
+pub fn res_0_a(cpu: &mut Processor, _instruction: u16) { reset_bit_register(cpu, _instruction, 0, Register::A); }
+
+pub fn res_1_b(cpu: &mut Processor, _instruction: u16) { reset_bit_register(cpu, _instruction, 1, Register::B); }
+pub fn res_1_c(cpu: &mut Processor, _instruction: u16) { reset_bit_register(cpu, _instruction, 1, Register::C); }
+pub fn res_1_d(cpu: &mut Processor, _instruction: u16) { reset_bit_register(cpu, _instruction, 1, Register::D); }
+pub fn res_1_e(cpu: &mut Processor, _instruction: u16) { reset_bit_register(cpu, _instruction, 1, Register::E); }
+pub fn res_1_h(cpu: &mut Processor, _instruction: u16) { reset_bit_register(cpu, _instruction, 1, Register::H); }
+pub fn res_1_l(cpu: &mut Processor, _instruction: u16) { reset_bit_register(cpu, _instruction, 1, Register::L); }
+pub fn res_1_hlp(cpu: &mut Processor, _instruction: u16) { reset_bit_hlp(cpu, _instruction, 1); }
+pub fn res_1_a(cpu: &mut Processor, _instruction: u16) { reset_bit_register(cpu, _instruction, 1, Register::A); }
+
+pub fn res_2_b(cpu: &mut Processor, _instruction: u16) { reset_bit_register(cpu, _instruction, 2, Register::B); }
+pub fn res_2_c(cpu: &mut Processor, _instruction: u16) { reset_bit_register(cpu, _instruction, 2, Register::C); }
+pub fn res_2_d(cpu: &mut Processor, _instruction: u16) { reset_bit_register(cpu, _instruction, 2, Register::D); }
+pub fn res_2_e(cpu: &mut Processor, _instruction: u16) { reset_bit_register(cpu, _instruction, 2, Register::E); }
+pub fn res_2_h(cpu: &mut Processor, _instruction: u16) { reset_bit_register(cpu, _instruction, 2, Register::H); }
+pub fn res_2_l(cpu: &mut Processor, _instruction: u16) { reset_bit_register(cpu, _instruction, 2, Register::L); }
+pub fn res_2_hlp(cpu: &mut Processor, _instruction: u16) { reset_bit_hlp(cpu, _instruction, 2); }
+pub fn res_2_a(cpu: &mut Processor, _instruction: u16) { reset_bit_register(cpu, _instruction, 2, Register::A); }
+
+pub fn res_3_b(cpu: &mut Processor, _instruction: u16) { reset_bit_register(cpu, _instruction, 3, Register::B); }
+pub fn res_3_c(cpu: &mut Processor, _instruction: u16) { reset_bit_register(cpu, _instruction, 3, Register::C); }
+pub fn res_3_d(cpu: &mut Processor, _instruction: u16) { reset_bit_register(cpu, _instruction, 3, Register::D); }
+pub fn res_3_e(cpu: &mut Processor, _instruction: u16) { reset_bit_register(cpu, _instruction, 3, Register::E); }
+pub fn res_3_h(cpu: &mut Processor, _instruction: u16) { reset_bit_register(cpu, _instruction, 3, Register::H); }
+pub fn res_3_l(cpu: &mut Processor, _instruction: u16) { reset_bit_register(cpu, _instruction, 3, Register::L); }
+pub fn res_3_hlp(cpu: &mut Processor, _instruction: u16) { reset_bit_hlp(cpu, _instruction, 3); }
+pub fn res_3_a(cpu: &mut Processor, _instruction: u16) { reset_bit_register(cpu, _instruction, 3, Register::A); }
+
+pub fn res_4_b(cpu: &mut Processor, _instruction: u16) { reset_bit_register(cpu, _instruction, 4, Register::B); }
+pub fn res_4_c(cpu: &mut Processor, _instruction: u16) { reset_bit_register(cpu, _instruction, 4, Register::C); }
+pub fn res_4_d(cpu: &mut Processor, _instruction: u16) { reset_bit_register(cpu, _instruction, 4, Register::D); }
+pub fn res_4_e(cpu: &mut Processor, _instruction: u16) { reset_bit_register(cpu, _instruction, 4, Register::E); }
+pub fn res_4_h(cpu: &mut Processor, _instruction: u16) { reset_bit_register(cpu, _instruction, 4, Register::H); }
+pub fn res_4_l(cpu: &mut Processor, _instruction: u16) { reset_bit_register(cpu, _instruction, 4, Register::L); }
+pub fn res_4_hlp(cpu: &mut Processor, _instruction: u16) { reset_bit_hlp(cpu, _instruction, 4); }
+pub fn res_4_a(cpu: &mut Processor, _instruction: u16) { reset_bit_register(cpu, _instruction, 4, Register::A); }
+
+pub fn res_5_b(cpu: &mut Processor, _instruction: u16) { reset_bit_register(cpu, _instruction, 5, Register::B); }
+pub fn res_5_c(cpu: &mut Processor, _instruction: u16) { reset_bit_register(cpu, _instruction, 5, Register::C); }
+pub fn res_5_d(cpu: &mut Processor, _instruction: u16) { reset_bit_register(cpu, _instruction, 5, Register::D); }
+pub fn res_5_e(cpu: &mut Processor, _instruction: u16) { reset_bit_register(cpu, _instruction, 5, Register::E); }
+pub fn res_5_h(cpu: &mut Processor, _instruction: u16) { reset_bit_register(cpu, _instruction, 5, Register::H); }
+pub fn res_5_l(cpu: &mut Processor, _instruction: u16) { reset_bit_register(cpu, _instruction, 5, Register::L); }
+pub fn res_5_hlp(cpu: &mut Processor, _instruction: u16) { reset_bit_hlp(cpu, _instruction, 5); }
+pub fn res_5_a(cpu: &mut Processor, _instruction: u16) { reset_bit_register(cpu, _instruction, 5, Register::A); }
+
+pub fn res_6_b(cpu: &mut Processor, _instruction: u16) { reset_bit_register(cpu, _instruction, 6, Register::B); }
+pub fn res_6_c(cpu: &mut Processor, _instruction: u16) { reset_bit_register(cpu, _instruction, 6, Register::C); }
+pub fn res_6_d(cpu: &mut Processor, _instruction: u16) { reset_bit_register(cpu, _instruction, 6, Register::D); }
+pub fn res_6_e(cpu: &mut Processor, _instruction: u16) { reset_bit_register(cpu, _instruction, 6, Register::E); }
+pub fn res_6_h(cpu: &mut Processor, _instruction: u16) { reset_bit_register(cpu, _instruction, 6, Register::H); }
+pub fn res_6_l(cpu: &mut Processor, _instruction: u16) { reset_bit_register(cpu, _instruction, 6, Register::L); }
+pub fn res_6_hlp(cpu: &mut Processor, _instruction: u16) { reset_bit_hlp(cpu, _instruction, 6); }
+pub fn res_6_a(cpu: &mut Processor, _instruction: u16) { reset_bit_register(cpu, _instruction, 6, Register::A); }
+
+pub fn res_7_b(cpu: &mut Processor, _instruction: u16) { reset_bit_register(cpu, _instruction, 7, Register::B); }
+pub fn res_7_c(cpu: &mut Processor, _instruction: u16) { reset_bit_register(cpu, _instruction, 7, Register::C); }
+pub fn res_7_d(cpu: &mut Processor, _instruction: u16) { reset_bit_register(cpu, _instruction, 7, Register::D); }
+pub fn res_7_e(cpu: &mut Processor, _instruction: u16) { reset_bit_register(cpu, _instruction, 7, Register::E); }
+pub fn res_7_h(cpu: &mut Processor, _instruction: u16) { reset_bit_register(cpu, _instruction, 7, Register::H); }
+pub fn res_7_l(cpu: &mut Processor, _instruction: u16) { reset_bit_register(cpu, _instruction, 7, Register::L); }
+pub fn res_7_hlp(cpu: &mut Processor, _instruction: u16) { reset_bit_hlp(cpu, _instruction, 7); }
+pub fn res_7_a(cpu: &mut Processor, _instruction: u16) { reset_bit_register(cpu, _instruction, 7, Register::A); }
+
+/// SET u3 r8
+pub fn set_bit_register(cpu: &mut Processor, _instruction: u16, bit: u8, register: Register) {
+    cpu.write_register(register, cpu.read_register(register) | (1 << bit));
+}
+
+/// SET u3 [HL]
+pub fn set_bit_hlp(cpu: &mut Processor, _instruction: u16, bit: u8) {
+    let memory_address = cpu.read_register16(RegisterPair::HL);
+    let value = cpu.read_memory(memory_address);
+
+    cpu.write_memory(memory_address, value | (1 << bit));
+}
+
+pub fn set_0_b(cpu: &mut Processor, _instruction: u16) { set_bit_register(cpu, _instruction, 0, Register::B); }
+pub fn set_0_c(cpu: &mut Processor, _instruction: u16) { set_bit_register(cpu, _instruction, 0, Register::C); }
+pub fn set_0_d(cpu: &mut Processor, _instruction: u16) { set_bit_register(cpu, _instruction, 0, Register::D); }
+pub fn set_0_e(cpu: &mut Processor, _instruction: u16) { set_bit_register(cpu, _instruction, 0, Register::E); }
+pub fn set_0_h(cpu: &mut Processor, _instruction: u16) { set_bit_register(cpu, _instruction, 0, Register::H); }
+pub fn set_0_l(cpu: &mut Processor, _instruction: u16) { set_bit_register(cpu, _instruction, 0, Register::L); }
+pub fn set_0_hlp(cpu: &mut Processor, _instruction: u16) { set_bit_hlp(cpu, _instruction, 0); }
+pub fn set_0_a(cpu: &mut Processor, _instruction: u16) { set_bit_register(cpu, _instruction, 0, Register::A); }
+
+pub fn set_1_b(cpu: &mut Processor, _instruction: u16) { set_bit_register(cpu, _instruction, 1, Register::B); }
+pub fn set_1_c(cpu: &mut Processor, _instruction: u16) { set_bit_register(cpu, _instruction, 1, Register::C); }
+pub fn set_1_d(cpu: &mut Processor, _instruction: u16) { set_bit_register(cpu, _instruction, 1, Register::D); }
+pub fn set_1_e(cpu: &mut Processor, _instruction: u16) { set_bit_register(cpu, _instruction, 1, Register::E); }
+pub fn set_1_h(cpu: &mut Processor, _instruction: u16) { set_bit_register(cpu, _instruction, 1, Register::H); }
+pub fn set_1_l(cpu: &mut Processor, _instruction: u16) { set_bit_register(cpu, _instruction, 1, Register::L); }
+pub fn set_1_hlp(cpu: &mut Processor, _instruction: u16) { set_bit_hlp(cpu, _instruction, 1); }
+pub fn set_1_a(cpu: &mut Processor, _instruction: u16) { set_bit_register(cpu, _instruction, 1, Register::A); }
+
+pub fn set_2_b(cpu: &mut Processor, _instruction: u16) { set_bit_register(cpu, _instruction, 2, Register::B); }
+pub fn set_2_c(cpu: &mut Processor, _instruction: u16) { set_bit_register(cpu, _instruction, 2, Register::C); }
+pub fn set_2_d(cpu: &mut Processor, _instruction: u16) { set_bit_register(cpu, _instruction, 2, Register::D); }
+pub fn set_2_e(cpu: &mut Processor, _instruction: u16) { set_bit_register(cpu, _instruction, 2, Register::E); }
+pub fn set_2_h(cpu: &mut Processor, _instruction: u16) { set_bit_register(cpu, _instruction, 2, Register::H); }
+pub fn set_2_l(cpu: &mut Processor, _instruction: u16) { set_bit_register(cpu, _instruction, 2, Register::L); }
+pub fn set_2_hlp(cpu: &mut Processor, _instruction: u16) { set_bit_hlp(cpu, _instruction, 2); }
+pub fn set_2_a(cpu: &mut Processor, _instruction: u16) { set_bit_register(cpu, _instruction, 2, Register::A); }
+
+pub fn set_3_b(cpu: &mut Processor, _instruction: u16) { set_bit_register(cpu, _instruction, 3, Register::B); }
+pub fn set_3_c(cpu: &mut Processor, _instruction: u16) { set_bit_register(cpu, _instruction, 3, Register::C); }
+pub fn set_3_d(cpu: &mut Processor, _instruction: u16) { set_bit_register(cpu, _instruction, 3, Register::D); }
+pub fn set_3_e(cpu: &mut Processor, _instruction: u16) { set_bit_register(cpu, _instruction, 3, Register::E); }
+pub fn set_3_h(cpu: &mut Processor, _instruction: u16) { set_bit_register(cpu, _instruction, 3, Register::H); }
+pub fn set_3_l(cpu: &mut Processor, _instruction: u16) { set_bit_register(cpu, _instruction, 3, Register::L); }
+pub fn set_3_hlp(cpu: &mut Processor, _instruction: u16) { set_bit_hlp(cpu, _instruction, 3); }
+pub fn set_3_a(cpu: &mut Processor, _instruction: u16) { set_bit_register(cpu, _instruction, 3, Register::A); }
+
+pub fn set_4_b(cpu: &mut Processor, _instruction: u16) { set_bit_register(cpu, _instruction, 4, Register::B); }
+pub fn set_4_c(cpu: &mut Processor, _instruction: u16) { set_bit_register(cpu, _instruction, 4, Register::C); }
+pub fn set_4_d(cpu: &mut Processor, _instruction: u16) { set_bit_register(cpu, _instruction, 4, Register::D); }
+pub fn set_4_e(cpu: &mut Processor, _instruction: u16) { set_bit_register(cpu, _instruction, 4, Register::E); }
+pub fn set_4_h(cpu: &mut Processor, _instruction: u16) { set_bit_register(cpu, _instruction, 4, Register::H); }
+pub fn set_4_l(cpu: &mut Processor, _instruction: u16) { set_bit_register(cpu, _instruction, 4, Register::L); }
+pub fn set_4_hlp(cpu: &mut Processor, _instruction: u16) { set_bit_hlp(cpu, _instruction, 4); }
+pub fn set_4_a(cpu: &mut Processor, _instruction: u16) { set_bit_register(cpu, _instruction, 4, Register::A); }
+
+pub fn set_5_b(cpu: &mut Processor, _instruction: u16) { set_bit_register(cpu, _instruction, 5, Register::B); }
+pub fn set_5_c(cpu: &mut Processor, _instruction: u16) { set_bit_register(cpu, _instruction, 5, Register::C); }
+pub fn set_5_d(cpu: &mut Processor, _instruction: u16) { set_bit_register(cpu, _instruction, 5, Register::D); }
+pub fn set_5_e(cpu: &mut Processor, _instruction: u16) { set_bit_register(cpu, _instruction, 5, Register::E); }
+pub fn set_5_h(cpu: &mut Processor, _instruction: u16) { set_bit_register(cpu, _instruction, 5, Register::H); }
+pub fn set_5_l(cpu: &mut Processor, _instruction: u16) { set_bit_register(cpu, _instruction, 5, Register::L); }
+pub fn set_5_hlp(cpu: &mut Processor, _instruction: u16) { set_bit_hlp(cpu, _instruction, 5); }
+pub fn set_5_a(cpu: &mut Processor, _instruction: u16) { set_bit_register(cpu, _instruction, 5, Register::A); }
+
+pub fn set_6_b(cpu: &mut Processor, _instruction: u16) { set_bit_register(cpu, _instruction, 6, Register::B); }
+pub fn set_6_c(cpu: &mut Processor, _instruction: u16) { set_bit_register(cpu, _instruction, 6, Register::C); }
+pub fn set_6_d(cpu: &mut Processor, _instruction: u16) { set_bit_register(cpu, _instruction, 6, Register::D); }
+pub fn set_6_e(cpu: &mut Processor, _instruction: u16) { set_bit_register(cpu, _instruction, 6, Register::E); }
+pub fn set_6_h(cpu: &mut Processor, _instruction: u16) { set_bit_register(cpu, _instruction, 6, Register::H); }
+pub fn set_6_l(cpu: &mut Processor, _instruction: u16) { set_bit_register(cpu, _instruction, 6, Register::L); }
+pub fn set_6_hlp(cpu: &mut Processor, _instruction: u16) { set_bit_hlp(cpu, _instruction, 6); }
+pub fn set_6_a(cpu: &mut Processor, _instruction: u16) { set_bit_register(cpu, _instruction, 6, Register::A); }
+
+pub fn set_7_b(cpu: &mut Processor, _instruction: u16) { set_bit_register(cpu, _instruction, 7, Register::B); }
+pub fn set_7_c(cpu: &mut Processor, _instruction: u16) { set_bit_register(cpu, _instruction, 7, Register::C); }
+pub fn set_7_d(cpu: &mut Processor, _instruction: u16) { set_bit_register(cpu, _instruction, 7, Register::D); }
+pub fn set_7_e(cpu: &mut Processor, _instruction: u16) { set_bit_register(cpu, _instruction, 7, Register::E); }
+pub fn set_7_h(cpu: &mut Processor, _instruction: u16) { set_bit_register(cpu, _instruction, 7, Register::H); }
+pub fn set_7_l(cpu: &mut Processor, _instruction: u16) { set_bit_register(cpu, _instruction, 7, Register::L); }
+pub fn set_7_hlp(cpu: &mut Processor, _instruction: u16) { set_bit_hlp(cpu, _instruction, 7); }
+pub fn set_7_a(cpu: &mut Processor, _instruction: u16) { set_bit_register(cpu, _instruction, 7, Register::A); }
+
 
 #[cfg(test)]
 mod test {
@@ -496,16 +1841,29 @@ mod test {
         }
     }
 
+    #[test]
+    fn increase_register_keeps_carry() {
+        let mut cpu = Processor::new();
+        cpu.write_register(Register::F, Flag::Z | Flag::C);
+        cpu.write_register(Register::B, 0x01);
+
+        inc_b(&mut cpu, 0);
+
+        assert!(cpu.test_flag(Flag::C));
+        assert!(!cpu.test_flag(Flag::Z));
+        assert!(!cpu.test_flag(Flag::N));
+    }
+
     #[test]
     fn increase_hl_pointer_works() {
         let mut cpu = Processor::new();
-        cpu.write_register(Register::H, 0x01);
+        cpu.write_register(Register::H, 0xC1);
         cpu.write_register(Register::L, 0x00);
-        cpu.write_memory(0x0100, 0x00);
+        cpu.write_memory(0xC100, 0x00);
 
         inc_hlp(&mut cpu, 0x0000);
 
-        assert_eq!(cpu.read_memory(0x0100), 0x1);
+        assert_eq!(cpu.read_memory(0xC100), 0x1);
     }
 
 
@@ -524,13 +1882,13 @@ mod test {
     #[test]
     fn decrease_hl_pointer_works() {
         let mut cpu = Processor::new();
-        cpu.write_register(Register::L, 0x01);
-        cpu.write_register(Register::H, 0x00);
-        cpu.write_memory(0x0100, 0x02);
+        cpu.write_register(Register::H, 0xC1);
+        cpu.write_register(Register::L, 0x00);
+        cpu.write_memory(0xC100, 0x02);
 
         dec_hlp(&mut cpu, 0x0000);
 
-        assert_eq!(cpu.read_memory(0x0100), 0x1);
+        assert_eq!(cpu.read_memory(0xC100), 0x1);
     }
 
     #[test]
@@ -544,9 +1902,9 @@ mod test {
         cpu.write_register(Register::L, 0xFF);
         cpu.stack_pointer = 0x00FF;
 
-        increase_double_register(&mut cpu, 0x0000, Register::B, Register::C);
-        increase_double_register(&mut cpu, 0x0000, Register::D, Register::E);
-        increase_double_register(&mut cpu, 0x0000, Register::H, Register::L);
+        increase_double_register(&mut cpu, 0x0000, RegisterPair::BC);
+        increase_double_register(&mut cpu, 0x0000, RegisterPair::DE);
+        increase_double_register(&mut cpu, 0x0000, RegisterPair::HL);
         inc_sp(&mut cpu, 0x0000);
 
         assert_eq!(cpu.read_register(Register::B), 0x01);
@@ -567,9 +1925,9 @@ mod test {
         cpu.stack_pointer = 0x0100;
 
 
-        decrease_double_register(&mut cpu, 0x0000, Register::B, Register::C);
-        decrease_double_register(&mut cpu, 0x0000, Register::D, Register::E);
-        decrease_double_register(&mut cpu, 0x0000, Register::H, Register::L);
+        decrease_double_register(&mut cpu, 0x0000, RegisterPair::BC);
+        decrease_double_register(&mut cpu, 0x0000, RegisterPair::DE);
+        decrease_double_register(&mut cpu, 0x0000, RegisterPair::HL);
         dec_sp(&mut cpu, 0x0000);
 
         assert_eq!(cpu.read_register(Register::C), 0xFF);
@@ -606,7 +1964,7 @@ mod test {
     fn load_double_register_value_works() {
         let mut cpu = Processor::new();
 
-        load_double_register_value(&mut cpu, 0xCDAB, Register::B, Register::C);
+        load_double_register_value(&mut cpu, 0xCDAB, RegisterPair::BC);
 
         assert_eq!(cpu.read_register(Register::B), 0xAB);
         assert_eq!(cpu.read_register(Register::C), 0xCD);
@@ -635,21 +1993,21 @@ mod test {
     #[test]
     fn load_hl_value_works() {
         let mut cpu = Processor::new();
-        cpu.write_register(Register::H, 0x01);
+        cpu.write_register(Register::H, 0xC1);
         cpu.write_register(Register::L, 0x00);
 
         ld_hlp_n8(&mut cpu, 0xAB00);
 
-        assert_eq!(cpu.read_memory(0x0100), 0xAB);
+        assert_eq!(cpu.read_memory(0xC100), 0xAB);
     }
 
     #[test]
     fn load_register_hlp_works() {
         let mut cpu = Processor::new();
-        cpu.write_register(Register::H, 0x01);
+        cpu.write_register(Register::H, 0xC1);
         cpu.write_register(Register::L, 0x00);
 
-        cpu.write_memory(0x0100, 0xAB);
+        cpu.write_memory(0xC100, 0xAB);
 
         load_register_hlp(&mut cpu, 0x0000, Register::B);
 
@@ -659,38 +2017,89 @@ mod test {
     #[test]
     fn load_double_registerp_a_works() {
         let mut cpu = Processor::new();
-        cpu.write_register(Register::B, 0x01);
+        cpu.write_register(Register::B, 0xC1);
         cpu.write_register(Register::C, 0x00);
         cpu.write_register(Register::A, 0xAB);
 
-        load_double_registerp_a(&mut cpu, 0x0000, Register::B, Register::C);
+        load_double_registerp_a(&mut cpu, 0x0000, RegisterPair::BC);
 
-        assert_eq!(cpu.read_memory(0x0100), 0xAB);
+        assert_eq!(cpu.read_memory(0xC100), 0xAB);
     }
 
     #[test]
-    fn load_hli_a_works() {
+    fn load_dep_a_uses_de() {
         let mut cpu = Processor::new();
-        cpu.write_register(Register::H, 0x00);
-        cpu.write_register(Register::L, 0xFF);
+        cpu.write_register16(RegisterPair::BC, 0xC000);
+        cpu.write_register16(RegisterPair::DE, 0xC100);
         cpu.write_register(Register::A, 0xAB);
 
-        ld_hli_a(&mut cpu, 0x0000);
+        ld_dep_a(&mut cpu, 0x0000);
 
-        assert_eq!(cpu.read_memory(0x00FF), 0xAB);
-        assert_eq!(cpu.read_register(Register::H), 0x01);
+        assert_eq!(cpu.read_memory(0xC100), 0xAB);
+        assert_eq!(cpu.read_memory(0xC000), 0x00);
     }
 
     #[test]
-    fn load_hld_a_works() {
+    fn load_a_double_registerp_works() {
         let mut cpu = Processor::new();
-        cpu.write_register(Register::H, 0x01);
+        cpu.write_register(Register::D, 0xC1);
+        cpu.write_register(Register::E, 0x00);
+        cpu.write_memory(0xC100, 0xAB);
+
+        ld_a_dep(&mut cpu, 0x0000);
+
+        assert_eq!(cpu.read_register(Register::A), 0xAB);
+    }
+
+    #[test]
+    fn load_a_hli_works() {
+        let mut cpu = Processor::new();
+        cpu.write_register(Register::H, 0xC0);
+        cpu.write_register(Register::L, 0xFF);
+        cpu.write_memory(0xC0FF, 0xAB);
+
+        ld_a_hli(&mut cpu, 0x0000);
+
+        assert_eq!(cpu.read_register(Register::A), 0xAB);
+        assert_eq!(cpu.read_register(Register::H), 0xC1);
+    }
+
+    #[test]
+    fn load_a_hld_works() {
+        let mut cpu = Processor::new();
+        cpu.write_register(Register::H, 0xC1);
+        cpu.write_register(Register::L, 0x00);
+        cpu.write_memory(0xC100, 0xAB);
+
+        ld_a_hld(&mut cpu, 0x0000);
+
+        assert_eq!(cpu.read_register(Register::A), 0xAB);
+        assert_eq!(cpu.read_register(Register::L), 0xFF);
+    }
+
+    #[test]
+    fn load_hli_a_works() {
+        let mut cpu = Processor::new();
+        cpu.write_register(Register::H, 0xC0);
+        cpu.write_register(Register::L, 0xFF);
+        cpu.write_register(Register::A, 0xAB);
+
+        ld_hli_a(&mut cpu, 0x0000);
+
+        assert_eq!(cpu.read_memory(0xC0FF), 0xAB);
+        assert_eq!(cpu.read_register(Register::H), 0xC1);
+    }
+
+    #[test]
+    fn load_hld_a_works() {
+        let mut cpu = Processor::new();
+        cpu.write_register(Register::H, 0xC1);
         cpu.write_register(Register::L, 0x00);
         cpu.write_register(Register::A, 0xAB);
 
         ld_hld_a(&mut cpu, 0x0000);
 
-        assert_eq!(cpu.read_memory(0x0100), 0xAB);
+        assert_eq!(cpu.read_memory(0xC100), 0xAB);
         assert_eq!(cpu.read_register(Register::L), 0xFF);
     }
 
@@ -699,9 +2108,19 @@ mod test {
         let mut cpu = Processor::new();
         cpu.write_register(Register::A, 0xAB);
 
-        ld_r16_a(&mut cpu, 0x0001);
+        ld_r16_a(&mut cpu, 0x00C1);
 
-        assert_eq!(cpu.read_memory(0x0100), 0xAB);
+        assert_eq!(cpu.read_memory(0xC100), 0xAB);
+    }
+
+    #[test]
+    fn load_a_double_value_works() {
+        let mut cpu = Processor::new();
+        cpu.write_memory(0xC100, 0xAB);
+
+        ld_a_r16(&mut cpu, 0x00C1);
+
+        assert_eq!(cpu.read_register(Register::A), 0xAB);
     }
 
     #[test]
@@ -750,10 +2169,12 @@ mod test {
     fn rlca_works() {
         let mut cpu = Processor::new();
         cpu.write_register(Register::A, 0b11001100);
+        cpu.write_register(Register::F, Flag::Z | Flag::N);
         rlca(&mut cpu, 0);
 
         let value = cpu.read_register(Register::A);
         assert_eq!(value, 0b10011001);
+        assert_eq!(cpu.read_flags(), Flag::C as u8);
     }
 
     #[test]
@@ -765,4 +2186,641 @@ mod test {
         let value = cpu.read_register(Register::A);
         assert_eq!(value, 0b01100110);
     }
+
+    #[test]
+    fn rla_works() {
+        let mut cpu = Processor::new();
+        cpu.write_register(Register::A, 0b11001100);
+        rla(&mut cpu, 0);
+
+        assert_eq!(cpu.read_register(Register::A), 0b10011000);
+        assert_eq!(cpu.read_flags() & Flag::C as u8, Flag::C as u8);
+
+        rla(&mut cpu, 0);
+
+        assert_eq!(cpu.read_register(Register::A), 0b00110001);
+    }
+
+    #[test]
+    fn rra_works() {
+        let mut cpu = Processor::new();
+        cpu.write_register(Register::A, 0b11001101);
+        rra(&mut cpu, 0);
+
+        assert_eq!(cpu.read_register(Register::A), 0b01100110);
+        assert_eq!(cpu.read_flags() & Flag::C as u8, Flag::C as u8);
+
+        rra(&mut cpu, 0);
+
+        assert_eq!(cpu.read_register(Register::A), 0b10110011);
+    }
+
+    #[test]
+    fn instruction_table_lengths_match_operands() {
+        assert_eq!(INSTRUCTIONS[0x00].bytes, 1);
+        assert_eq!(INSTRUCTIONS[0x06].bytes, 2);
+        assert_eq!(INSTRUCTIONS[0x01].bytes, 3);
+        assert_eq!(INSTRUCTIONS[0xE0].bytes, 2);
+        assert_eq!(INSTRUCTIONS[0xFA].bytes, 3);
+        assert_eq!(INSTRUCTIONS[0x36].cycles, 12);
+    }
+
+    #[test]
+    fn rotate_circular_works() {
+        let mut cpu = Processor::new();
+        cpu.write_register(Register::B, 0b1000_0001);
+
+        rlc_b(&mut cpu, 0);
+        assert_eq!(cpu.read_register(Register::B), 0b0000_0011);
+        assert_eq!(cpu.read_flags(), Flag::C as u8);
+
+        rrc_b(&mut cpu, 0);
+        rrc_b(&mut cpu, 0);
+        assert_eq!(cpu.read_register(Register::B), 0b1100_0000);
+        assert_eq!(cpu.read_flags(), Flag::C as u8);
+
+        cpu.write_register(Register::C, 0x00);
+        rlc_c(&mut cpu, 0);
+        assert_eq!(cpu.read_flags(), Flag::Z as u8);
+    }
+
+    #[test]
+    fn rotate_through_carry_works() {
+        let mut cpu = Processor::new();
+        cpu.write_register(Register::D, 0b1000_0000);
+
+        rl_d(&mut cpu, 0);
+        assert_eq!(cpu.read_register(Register::D), 0x00);
+        assert_eq!(cpu.read_flags(), Flag::Z | Flag::C);
+
+        rl_d(&mut cpu, 0);
+        assert_eq!(cpu.read_register(Register::D), 0x01);
+        assert_eq!(cpu.read_flags(), 0);
+
+        rr_d(&mut cpu, 0);
+        assert_eq!(cpu.read_register(Register::D), 0x00);
+        assert_eq!(cpu.read_flags(), Flag::Z | Flag::C);
+
+        rr_d(&mut cpu, 0);
+        assert_eq!(cpu.read_register(Register::D), 0x80);
+        assert_eq!(cpu.read_flags(), 0);
+    }
+
+    #[test]
+    fn shifts_work() {
+        let mut cpu = Processor::new();
+        cpu.write_register(Register::E, 0b1000_0001);
+
+        sla_e(&mut cpu, 0);
+        assert_eq!(cpu.read_register(Register::E), 0b0000_0010);
+        assert_eq!(cpu.read_flags(), Flag::C as u8);
+
+        cpu.write_register(Register::E, 0b1000_0001);
+        sra_e(&mut cpu, 0);
+        assert_eq!(cpu.read_register(Register::E), 0b1100_0000);
+        assert_eq!(cpu.read_flags(), Flag::C as u8);
+
+        cpu.write_register(Register::E, 0b1000_0001);
+        srl_e(&mut cpu, 0);
+        assert_eq!(cpu.read_register(Register::E), 0b0100_0000);
+        assert_eq!(cpu.read_flags(), Flag::C as u8);
+
+        cpu.write_register(Register::E, 0b0000_0001);
+        srl_e(&mut cpu, 0);
+        assert_eq!(cpu.read_flags(), Flag::Z | Flag::C);
+    }
+
+    #[test]
+    fn swap_works() {
+        let mut cpu = Processor::new();
+        cpu.write_register(Register::A, 0xAB);
+        cpu.write_register(Register::F, 0xF0);
+
+        swap_a(&mut cpu, 0);
+        assert_eq!(cpu.read_register(Register::A), 0xBA);
+        assert_eq!(cpu.read_flags(), 0);
+
+        cpu.write_register(Register::A, 0x00);
+        swap_a(&mut cpu, 0);
+        assert_eq!(cpu.read_flags(), Flag::Z as u8);
+    }
+
+    #[test]
+    fn cb_hlp_works() {
+        let mut cpu = Processor::new();
+        cpu.write_register(Register::H, 0xC0);
+        cpu.write_register(Register::L, 0x10);
+        cpu.write_memory(0xC010, 0x0F);
+
+        swap_hlp(&mut cpu, 0);
+        assert_eq!(cpu.read_memory(0xC010), 0xF0);
+
+        res_7_hlp(&mut cpu, 0);
+        assert_eq!(cpu.read_memory(0xC010), 0x70);
+
+        set_0_hlp(&mut cpu, 0);
+        assert_eq!(cpu.read_memory(0xC010), 0x71);
+    }
+
+    #[test]
+    fn test_bit_works() {
+        let mut cpu = Processor::new();
+        cpu.write_register(Register::H, 0b0000_0100);
+        cpu.write_register(Register::F, Flag::C as u8);
+
+        bit_2_h(&mut cpu, 0);
+        assert_eq!(cpu.read_flags(), Flag::H | Flag::C);
+
+        bit_3_h(&mut cpu, 0);
+        assert_eq!(cpu.read_flags(), Flag::Z as u8 | Flag::H as u8 | Flag::C as u8);
+        assert_eq!(cpu.read_register(Register::H), 0b0000_0100);
+    }
+
+    #[test]
+    fn reset_and_set_bit_work() {
+        let mut cpu = Processor::new();
+        cpu.write_register(Register::L, 0x00);
+
+        set_3_l(&mut cpu, 0);
+        set_7_l(&mut cpu, 0);
+        assert_eq!(cpu.read_register(Register::L), 0x88);
+
+        res_3_l(&mut cpu, 0);
+        assert_eq!(cpu.read_register(Register::L), 0x80);
+    }
+
+    #[test]
+    fn cb_table_cycles_work() {
+        assert_eq!(CB_INSTRUCTIONS[0x00].cycles, 8);
+        assert_eq!(CB_INSTRUCTIONS[0x06].cycles, 16);
+        assert_eq!(CB_INSTRUCTIONS[0x46].cycles, 12);
+        assert_eq!(CB_INSTRUCTIONS[0x86].cycles, 16);
+        assert_eq!(CB_INSTRUCTIONS[0xFE].cycles, 16);
+    }
+
+    #[test]
+    fn prefix_cb_works() {
+        let mut cpu = Processor::new();
+        cpu.write_register(Register::B, 0x0F);
+
+        // SWAP B through the base table
+        (INSTRUCTIONS[0xCB].operation)(&mut cpu, 0x3000);
+        assert_eq!(cpu.read_register(Register::B), 0xF0);
+    }
+
+    #[test]
+    fn add_works() {
+        let mut cpu = Processor::new();
+        cpu.write_register(Register::A, 0x3A);
+        cpu.write_register(Register::B, 0xC6);
+
+        add_a_b(&mut cpu, 0);
+        assert_eq!(cpu.read_register(Register::A), 0x00);
+        assert_eq!(cpu.read_flags(), Flag::Z as u8 | Flag::H as u8 | Flag::C as u8);
+
+        cpu.write_register(Register::A, 0x3C);
+        add_a_n8(&mut cpu, 0xFF00);
+        assert_eq!(cpu.read_register(Register::A), 0x3B);
+        assert_eq!(cpu.read_flags(), Flag::H | Flag::C);
+
+        cpu.write_register(Register::A, 0x12);
+        add_a_n8(&mut cpu, 0x2200);
+        assert_eq!(cpu.read_register(Register::A), 0x34);
+        assert_eq!(cpu.read_flags(), 0);
+    }
+
+    #[test]
+    fn add_with_carry_works() {
+        let mut cpu = Processor::new();
+        cpu.write_register(Register::A, 0xE1);
+        cpu.write_register(Register::E, 0x0F);
+        cpu.write_register(Register::F, Flag::C as u8);
+
+        adc_a_e(&mut cpu, 0);
+        assert_eq!(cpu.read_register(Register::A), 0xF1);
+        assert_eq!(cpu.read_flags(), Flag::H as u8);
+
+        // Carry in alone produces the half carry
+        cpu.write_register(Register::A, 0x0F);
+        cpu.write_register(Register::F, Flag::C as u8);
+        adc_a_n8(&mut cpu, 0x0000);
+        assert_eq!(cpu.read_register(Register::A), 0x10);
+        assert_eq!(cpu.read_flags(), Flag::H as u8);
+
+        cpu.write_register(Register::A, 0xFF);
+        cpu.write_register(Register::F, Flag::C as u8);
+        adc_a_n8(&mut cpu, 0x0000);
+        assert_eq!(cpu.read_register(Register::A), 0x00);
+        assert_eq!(cpu.read_flags(), Flag::Z as u8 | Flag::H as u8 | Flag::C as u8);
+    }
+
+    #[test]
+    fn subtract_works() {
+        let mut cpu = Processor::new();
+        cpu.write_register(Register::A, 0x3E);
+        cpu.write_register(Register::E, 0x3E);
+
+        sub_a_e(&mut cpu, 0);
+        assert_eq!(cpu.read_register(Register::A), 0x00);
+        assert_eq!(cpu.read_flags(), Flag::Z | Flag::N);
+
+        cpu.write_register(Register::A, 0x3E);
+        sub_a_n8(&mut cpu, 0x0F00);
+        assert_eq!(cpu.read_register(Register::A), 0x2F);
+        assert_eq!(cpu.read_flags(), Flag::N | Flag::H);
+
+        cpu.write_register(Register::A, 0x3E);
+        sub_a_n8(&mut cpu, 0x4000);
+        assert_eq!(cpu.read_register(Register::A), 0xFE);
+        assert_eq!(cpu.read_flags(), Flag::N | Flag::C);
+    }
+
+    #[test]
+    fn subtract_with_carry_works() {
+        let mut cpu = Processor::new();
+        cpu.write_register(Register::A, 0x3B);
+        cpu.write_register(Register::H, 0x2A);
+        cpu.write_register(Register::F, Flag::C as u8);
+
+        sbc_a_h(&mut cpu, 0);
+        assert_eq!(cpu.read_register(Register::A), 0x10);
+        assert_eq!(cpu.read_flags(), Flag::N as u8);
+
+        cpu.write_register(Register::A, 0x3B);
+        cpu.write_register(Register::F, Flag::C as u8);
+        sbc_a_n8(&mut cpu, 0x3A00);
+        assert_eq!(cpu.read_register(Register::A), 0x00);
+        assert_eq!(cpu.read_flags(), Flag::Z | Flag::N);
+
+        cpu.write_register(Register::A, 0x3B);
+        cpu.write_register(Register::F, Flag::C as u8);
+        sbc_a_n8(&mut cpu, 0x4F00);
+        assert_eq!(cpu.read_register(Register::A), 0xEB);
+        assert_eq!(cpu.read_flags(), Flag::N as u8 | Flag::H as u8 | Flag::C as u8);
+    }
+
+    #[test]
+    fn logic_works() {
+        let mut cpu = Processor::new();
+        cpu.write_register(Register::A, 0x5A);
+        cpu.write_register(Register::L, 0x3F);
+
+        and_a_l(&mut cpu, 0);
+        assert_eq!(cpu.read_register(Register::A), 0x1A);
+        assert_eq!(cpu.read_flags(), Flag::H as u8);
+
+        xor_a_a(&mut cpu, 0);
+        assert_eq!(cpu.read_register(Register::A), 0x00);
+        assert_eq!(cpu.read_flags(), Flag::Z as u8);
+
+        or_a_n8(&mut cpu, 0x5A00);
+        assert_eq!(cpu.read_register(Register::A), 0x5A);
+        assert_eq!(cpu.read_flags(), 0);
+    }
+
+    #[test]
+    fn compare_works() {
+        let mut cpu = Processor::new();
+        cpu.write_register(Register::A, 0x3C);
+        cpu.write_register(Register::H, 0xC0);
+        cpu.write_register(Register::L, 0x00);
+        cpu.write_memory(0xC000, 0x40);
+
+        cp_a_hlp(&mut cpu, 0);
+        assert_eq!(cpu.read_register(Register::A), 0x3C);
+        assert_eq!(cpu.read_flags(), Flag::N | Flag::C);
+
+        cp_a_n8(&mut cpu, 0x3C00);
+        assert_eq!(cpu.read_flags(), Flag::Z | Flag::N);
+    }
+
+    #[test]
+    fn daa_works() {
+        let mut cpu = Processor::new();
+
+        // 0x45 + 0x38 = 0x83 in BCD
+        cpu.write_register(Register::A, 0x45);
+        add_a_n8(&mut cpu, 0x3800);
+        daa(&mut cpu, 0);
+        assert_eq!(cpu.read_register(Register::A), 0x83);
+        assert_eq!(cpu.read_flags(), 0);
+
+        // 0x83 - 0x38 = 0x45 in BCD
+        sub_a_n8(&mut cpu, 0x3800);
+        daa(&mut cpu, 0);
+        assert_eq!(cpu.read_register(Register::A), 0x45);
+        assert_eq!(cpu.read_flags(), Flag::N as u8);
+
+        // 0x99 + 0x01 = 0x00 with carry in BCD
+        cpu.write_register(Register::A, 0x99);
+        add_a_n8(&mut cpu, 0x0100);
+        daa(&mut cpu, 0);
+        assert_eq!(cpu.read_register(Register::A), 0x00);
+        assert_eq!(cpu.read_flags(), Flag::Z | Flag::C);
+    }
+
+    #[test]
+    fn cpl_works() {
+        let mut cpu = Processor::new();
+        cpu.write_register(Register::A, 0x35);
+        cpu.write_register(Register::F, Flag::Z | Flag::C);
+
+        cpl(&mut cpu, 0);
+        assert_eq!(cpu.read_register(Register::A), 0xCA);
+        assert_eq!(cpu.read_flags(), 0xF0);
+    }
+
+    #[test]
+    fn carry_flag_instructions_work() {
+        let mut cpu = Processor::new();
+        cpu.write_register(Register::F, Flag::Z | Flag::N);
+
+        scf(&mut cpu, 0);
+        assert_eq!(cpu.read_flags(), Flag::Z | Flag::C);
+
+        ccf(&mut cpu, 0);
+        assert_eq!(cpu.read_flags(), Flag::Z as u8);
+
+        ccf(&mut cpu, 0);
+        assert_eq!(cpu.read_flags(), Flag::Z | Flag::C);
+    }
+
+    #[test]
+    fn jump_works() {
+        let mut cpu = Processor::new();
+
+        jp_n16(&mut cpu, 0x3412);
+        assert_eq!(cpu.program_counter, 0x1234);
+        assert!(cpu.branch_taken);
+
+        cpu.branch_taken = false;
+        cpu.write_register(Register::F, Flag::Z as u8);
+        jp_nz_n16(&mut cpu, 0x0000);
+        assert_eq!(cpu.program_counter, 0x1234);
+        assert!(!cpu.branch_taken);
+
+        jp_z_n16(&mut cpu, 0x00C0);
+        assert_eq!(cpu.program_counter, 0xC000);
+        assert!(cpu.branch_taken);
+
+        cpu.write_register(Register::H, 0xAB);
+        cpu.write_register(Register::L, 0xCD);
+        jp_hl(&mut cpu, 0);
+        assert_eq!(cpu.program_counter, 0xABCD);
+    }
+
+    #[test]
+    fn jump_relative_works() {
+        let mut cpu = Processor::new();
+        cpu.program_counter = 0x0100;
+
+        jr_e8(&mut cpu, 0x0500);
+        assert_eq!(cpu.program_counter, 0x0105);
+
+        jr_e8(&mut cpu, 0xFB00);
+        assert_eq!(cpu.program_counter, 0x0100);
+
+        cpu.branch_taken = false;
+        cpu.write_register(Register::F, Flag::C as u8);
+        jr_nc_e8(&mut cpu, 0x1000);
+        assert_eq!(cpu.program_counter, 0x0100);
+        assert!(!cpu.branch_taken);
+
+        jr_c_e8(&mut cpu, 0x1000);
+        assert_eq!(cpu.program_counter, 0x0110);
+        assert!(cpu.branch_taken);
+    }
+
+    #[test]
+    fn call_and_return_work() {
+        let mut cpu = Processor::new();
+        cpu.stack_pointer = 0xD000;
+        cpu.program_counter = 0x0203;
+
+        call_n16(&mut cpu, 0x0040);
+        assert_eq!(cpu.program_counter, 0x4000);
+        assert_eq!(cpu.stack_pointer, 0xCFFE);
+
+        cpu.branch_taken = false;
+        cpu.write_register(Register::F, Flag::C as u8);
+        call_nc_n16(&mut cpu, 0x0050);
+        ret_nc(&mut cpu, 0);
+        assert_eq!(cpu.program_counter, 0x4000);
+        assert!(!cpu.branch_taken);
+
+        ret_c(&mut cpu, 0);
+        assert_eq!(cpu.program_counter, 0x0203);
+        assert_eq!(cpu.stack_pointer, 0xD000);
+        assert!(cpu.branch_taken);
+    }
+
+    #[test]
+    fn restart_works() {
+        let mut cpu = Processor::new();
+        cpu.stack_pointer = 0xD000;
+        cpu.program_counter = 0x1235;
+
+        rst_38(&mut cpu, 0);
+        assert_eq!(cpu.program_counter, 0x0038);
+
+        reti(&mut cpu, 0);
+        assert_eq!(cpu.program_counter, 0x1235);
+    }
+
+    #[test]
+    fn load_value_stackpointer_works() {
+        let mut cpu = Processor::new();
+        cpu.stack_pointer = 0xABCD;
+
+        ld_n16_sp(&mut cpu, 0x00C1);
+
+        assert_eq!(cpu.read_memory(0xC100), 0xCD);
+        assert_eq!(cpu.read_memory(0xC101), 0xAB);
+    }
+
+    #[test]
+    fn load_stackpointer_hl_works() {
+        let mut cpu = Processor::new();
+        cpu.write_register(Register::H, 0xAB);
+        cpu.write_register(Register::L, 0xCD);
+
+        ld_sp_hl(&mut cpu, 0);
+
+        assert_eq!(cpu.stack_pointer, 0xABCD);
+    }
+
+    #[test]
+    fn push_and_pop_work() {
+        let mut cpu = Processor::new();
+        cpu.stack_pointer = 0xD000;
+        cpu.write_register(Register::B, 0xAB);
+        cpu.write_register(Register::C, 0xCD);
+
+        push_bc(&mut cpu, 0);
+        assert_eq!(cpu.stack_pointer, 0xCFFE);
+        assert_eq!(cpu.read_memory(0xCFFF), 0xAB);
+        assert_eq!(cpu.read_memory(0xCFFE), 0xCD);
+
+        pop_hl(&mut cpu, 0);
+        assert_eq!(cpu.stack_pointer, 0xD000);
+        assert_eq!(cpu.read_register(Register::H), 0xAB);
+        assert_eq!(cpu.read_register(Register::L), 0xCD);
+    }
+
+    #[test]
+    fn pop_af_masks_flags() {
+        let mut cpu = Processor::new();
+        cpu.stack_pointer = 0xD000;
+        cpu.write_register(Register::B, 0x12);
+        cpu.write_register(Register::C, 0xFF);
+
+        push_bc(&mut cpu, 0);
+        pop_af(&mut cpu, 0);
+
+        assert_eq!(cpu.read_register(Register::A), 0x12);
+        assert_eq!(cpu.read_register(Register::F), 0xF0);
+    }
+
+    #[test]
+    fn add_hl_works() {
+        let mut cpu = Processor::new();
+        cpu.write_register(Register::H, 0x8A);
+        cpu.write_register(Register::L, 0x23);
+        cpu.write_register(Register::F, Flag::Z | Flag::N);
+
+        add_hl_hl(&mut cpu, 0);
+        assert_eq!(cpu.read_register(Register::H), 0x14);
+        assert_eq!(cpu.read_register(Register::L), 0x46);
+        assert_eq!(cpu.read_flags(), Flag::Z as u8 | Flag::H as u8 | Flag::C as u8);
+
+        cpu.write_register(Register::D, 0x00);
+        cpu.write_register(Register::E, 0x01);
+        add_hl_de(&mut cpu, 0);
+        assert_eq!(cpu.read_register(Register::L), 0x47);
+        assert_eq!(cpu.read_flags(), Flag::Z as u8);
+
+        cpu.stack_pointer = 0x0FFF;
+        add_hl_sp(&mut cpu, 0);
+        assert_eq!(cpu.read_register(Register::H), 0x24);
+        assert_eq!(cpu.read_flags(), Flag::Z | Flag::H);
+    }
+
+    #[test]
+    fn add_stackpointer_offset_works() {
+        let mut cpu = Processor::new();
+        cpu.stack_pointer = 0xFFF8;
+
+        add_sp_e8(&mut cpu, 0x0200);
+        assert_eq!(cpu.stack_pointer, 0xFFFA);
+        assert_eq!(cpu.read_flags(), 0);
+
+        // -1 carries out of both nibble and byte
+        add_sp_e8(&mut cpu, 0xFF00);
+        assert_eq!(cpu.stack_pointer, 0xFFF9);
+        assert_eq!(cpu.read_flags(), Flag::H | Flag::C);
+    }
+
+    #[test]
+    fn load_hl_stackpointer_offset_works() {
+        let mut cpu = Processor::new();
+        cpu.stack_pointer = 0x00FF;
+        cpu.write_register(Register::F, Flag::Z | Flag::N);
+
+        ld_hl_sp_e8(&mut cpu, 0x0100);
+
+        assert_eq!(cpu.read_register(Register::H), 0x01);
+        assert_eq!(cpu.read_register(Register::L), 0x00);
+        assert_eq!(cpu.read_flags(), Flag::H | Flag::C);
+        assert_eq!(cpu.stack_pointer, 0x00FF);
+    }
+
+    #[test]
+    fn increase_register_wraps() {
+        let mut cpu = Processor::new();
+        cpu.write_register(Register::A, 0xFF);
+        cpu.write_register(Register::F, Flag::N | Flag::C);
+
+        inc_a(&mut cpu, 0);
+
+        assert_eq!(cpu.read_register(Register::A), 0x00);
+        assert_eq!(cpu.read_flags(), Flag::Z as u8 | Flag::H as u8 | Flag::C as u8);
+    }
+
+    #[test]
+    fn increase_register_half_carry_boundaries() {
+        let mut cpu = Processor::new();
+        for (value, half_carry) in [(0x00, false), (0x0E, false), (0x0F, true), (0x10, false), (0x7F, true), (0xFE, false)] {
+            cpu.write_register(Register::B, value);
+            inc_b(&mut cpu, 0);
+
+            assert_eq!(cpu.read_register(Register::B), value + 1);
+            assert_eq!(cpu.test_flag(Flag::H), half_carry);
+            assert!(!cpu.test_flag(Flag::Z));
+        }
+    }
+
+    #[test]
+    fn decrease_register_wraps() {
+        let mut cpu = Processor::new();
+        cpu.write_register(Register::A, 0x00);
+
+        dec_a(&mut cpu, 0);
+
+        assert_eq!(cpu.read_register(Register::A), 0xFF);
+        assert_eq!(cpu.read_flags(), Flag::N | Flag::H);
+
+        cpu.write_register(Register::A, 0x01);
+        dec_a(&mut cpu, 0);
+
+        assert_eq!(cpu.read_register(Register::A), 0x00);
+        assert_eq!(cpu.read_flags(), Flag::Z | Flag::N);
+    }
+
+    #[test]
+    fn decrease_register_half_carry_boundaries() {
+        let mut cpu = Processor::new();
+        for (value, half_carry) in [(0x01, false), (0x0F, false), (0x10, true), (0x11, false), (0x80, true), (0xFF, false)] {
+            cpu.write_register(Register::C, value);
+            dec_c(&mut cpu, 0);
+
+            assert_eq!(cpu.read_register(Register::C), value - 1);
+            assert_eq!(cpu.test_flag(Flag::H), half_carry);
+        }
+    }
+
+    #[test]
+    fn hl_pointer_wraps() {
+        let mut cpu = Processor::new();
+        cpu.write_register16(RegisterPair::HL, 0xC000);
+        cpu.write_memory(0xC000, 0xFF);
+
+        inc_hlp(&mut cpu, 0);
+        assert_eq!(cpu.read_memory(0xC000), 0x00);
+        assert_eq!(cpu.read_flags(), Flag::Z | Flag::H);
+
+        dec_hlp(&mut cpu, 0);
+        assert_eq!(cpu.read_memory(0xC000), 0xFF);
+        assert_eq!(cpu.read_flags(), Flag::N | Flag::H);
+    }
+
+    #[test]
+    fn double_register_wraps() {
+        let mut cpu = Processor::new();
+        cpu.write_register16(RegisterPair::BC, 0x0000);
+        cpu.write_register16(RegisterPair::HL, 0xFFFF);
+        cpu.stack_pointer = 0xFFFF;
+        cpu.write_register(Register::F, Flag::Z as u8);
+
+        dec_bc(&mut cpu, 0);
+        inc_hl(&mut cpu, 0);
+        inc_sp(&mut cpu, 0);
+
+        assert_eq!(cpu.read_register16(RegisterPair::BC), 0xFFFF);
+        assert_eq!(cpu.read_register16(RegisterPair::HL), 0x0000);
+        assert_eq!(cpu.stack_pointer, 0x0000);
+
+        dec_sp(&mut cpu, 0);
+        assert_eq!(cpu.stack_pointer, 0xFFFF);
+        assert_eq!(cpu.read_flags(), Flag::Z as u8);
+    }
 }
@@ -1,8 +1,8 @@
-// https://gbdev.io/pandocs/Memory_Map.html
 use core::ops::{ BitOr, BitAnd };
-use std::fs::File;
-use std::io::Read;
-use std::path::Path;
+
+use crate::bus::{Bus, Mmu};
+use crate::instructions::{CB_INSTRUCTIONS, INSTRUCTIONS};
+use crate::cartridge::{Cartridge, CartridgeError, LoadMode};
 
 #[derive(Clone, Copy, PartialEq)]
 pub enum Register {
@@ -16,7 +16,31 @@ pub enum Register {
     L = 7,
 }
 
-pub enum Flag { 
+#[derive(Clone, Copy, PartialEq)]
+pub enum RegisterPair {
+    BC,
+    DE,
+    HL,
+    AF,
+    SP,
+    PC,
+}
+
+impl RegisterPair {
+    /// High and low 8 bit registers, SP and PC have none
+    fn registers(self) -> (Register, Register) {
+        match self {
+            RegisterPair::BC => (Register::B, Register::C),
+            RegisterPair::DE => (Register::D, Register::E),
+            RegisterPair::HL => (Register::H, Register::L),
+            RegisterPair::AF => (Register::A, Register::F),
+            RegisterPair::SP | RegisterPair::PC => unreachable!("not a register pair"),
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum Flag {
     Z  = 0b1000_0000,
     N  = 0b0100_0000,
     H  = 0b0010_0000,
@@ -36,131 +60,242 @@ impl BitOr for Flag {
     }
 }
 
+pub const JOYPAD: u16 = 0xFF00;
+pub const DIVIDER: u16 = 0xFF04;
+pub const INTERRUPT_FLAG: u16 = 0xFF0F;
+pub const INTERRUPT_ENABLE: u16 = 0xFFFF;
+
+/// Bits of IE and IF, declared from highest to lowest priority
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Interrupt {
+    VBlank = 0b0000_0001,
+    Stat   = 0b0000_0010,
+    Timer  = 0b0000_0100,
+    Serial = 0b0000_1000,
+    Joypad = 0b0001_0000,
+}
+
+impl Interrupt {
+    pub const ALL: [Interrupt; 5] = [
+        Interrupt::VBlank,
+        Interrupt::Stat,
+        Interrupt::Timer,
+        Interrupt::Serial,
+        Interrupt::Joypad,
+    ];
+
+    pub fn vector(self) -> u16 {
+        match self {
+            Interrupt::VBlank => 0x0040,
+            Interrupt::Stat => 0x0048,
+            Interrupt::Timer => 0x0050,
+            Interrupt::Serial => 0x0058,
+            Interrupt::Joypad => 0x0060,
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum State {
+    Running,
+    // HALT, until an interrupt is pending
+    Halted,
+    // STOP, until a joypad line goes low
+    Stopped,
+    // Illegal opcode, nothing but a reset gets out of it
+    Locked,
+}
+
 pub struct Processor {
     pub stack_pointer: u16,
-    program_counter: u16,
+    pub program_counter: u16,
+
+    // Set by conditional instructions when their branch is taken
+    pub branch_taken: bool,
+
+    // IME, only changed by EI, DI, RETI and interrupt dispatch
+    pub interrupt_master_enable: bool,
+    // EI sets IME after the instruction that follows it
+    pub interrupt_enable_scheduled: bool,
+
+    pub state: State,
+    // HALT with IME off and an interrupt pending skips the next PC increment
+    pub halt_bug: bool,
 
     registers: Box<[u8; 8]>,
 
-    memory: Box<[u8; 0x1_0000]>,
+    memory: Mmu,
 }
 
-/* 
- * [0000, 3FFF] ROM Bank 00 -> Cartridge
- * [4000, 7FFF] ROM Bank 01~NN -> Cartridge
- * [8000, 9FFF] VRAM
- * [A000, BFFF] External RAM -> Cartridge
- * [C000, CFFF] Work RAM
- * [D000, DFFF] Work RAM
- * [E000, FDFF] ECHO RAM - Mirror of C000~DDFF - Prohibited Access
- * [FE00, FE9F] OAM
- * [FEA0, FEFF] Not Usable - Prohibited
- * [FF00, FF7F] I/O Registers
- * [FF80, FFFE] High RAM
- * [FFFF] Interrupt Enable Register
- */
-
-// Cartridge Header => [0100, 014F]
-/* [0100, 0103] Entry Point
- * [0104, 0133] Nintendo Logo - MUST HAVE
- * [0134, 0143] Game Tittle - OLD Cartridges
- * [013F, 0142] Manifacturer Code - NEWER Cartridges
- * [0143] CGB Flag - NEWER Cartridges
- * [0144, 0145] New Licensee Code
- * [0146] SGB Flag
- * [0147] Cartridge Type
- * [0148] ROM size
- * [0149] RAM size
- * [014A] Destination code
- * [014B] Old Licensee Code
- * [014C] ROM version number
- * [014D] Header Checksum
- * [014E, 014F] Global Checksum
- */
+impl Default for Processor {
+    fn default() -> Self {
+        Self::new()
+    }
+}
 
 impl Processor {
     pub fn new() -> Self {
         Processor {
             stack_pointer: 0,
             program_counter: 0,
+            branch_taken: false,
+            interrupt_master_enable: false,
+            interrupt_enable_scheduled: false,
+            state: State::Running,
+            halt_bug: false,
             registers: Box::new([0; 8]),
-            memory: Box::new([0; 0x1_0000]),
+            memory: Mmu::new(),
         }
     }
 
-    pub fn load_cartridge(&mut self, path: &str) {
-        let global_path = Path::new(path);
+    /// Insert the rom at path, returning the warnings Lenient mode let through
+    pub fn load_cartridge(&mut self, path: &str, mode: LoadMode) -> Result<Vec<CartridgeError>, CartridgeError> {
+        let (cartridge, warnings) = Cartridge::open(path, mode)?;
+        self.insert_cartridge(cartridge);
+        Ok(warnings)
+    }
+
+    pub fn insert_cartridge(&mut self, cartridge: Cartridge) {
+        self.memory.insert_cartridge(Box::new(cartridge));
+    }
+
+    /// For the frontend to reach save data and cartridge hardware
+    pub fn cartridge(&self) -> Option<&Cartridge> {
+        self.memory.cartridge()
+    }
+
+    pub fn cartridge_mut(&mut self) -> Option<&mut Cartridge> {
+        self.memory.cartridge_mut()
+    }
+
+    /// Fetch, decode and execute one instruction, returning the cycles it took
+    pub fn step(&mut self) -> u8 {
+        let cycles = self.execute();
+        // Clocks on the bus, like the cartridge RTC, run alongside the CPU
+        self.memory.tick(cycles as u32);
+        cycles
+    }
+
+    fn execute(&mut self) -> u8 {
+        match self.state {
+            State::Running => (),
+            State::Halted => {
+                if self.pending_interrupts() == 0 { return 4; }
+                self.state = State::Running;
+            }
+            State::Stopped => {
+                if !self.joypad_pressed() { return 4; }
+                self.state = State::Running;
+            }
+            State::Locked => return 4,
+        }
 
-        let mut file = match File::open(&global_path) {
-            Err(why) => panic!("couldn't open {}: {}", global_path.display(),why),
-            Ok(file) => file,
+        let enable_interrupts = self.interrupt_enable_scheduled;
+
+        let interrupt_cycles = self.handle_interrupts();
+        if interrupt_cycles > 0 {
+            return interrupt_cycles;
+        }
+
+        let opcode = self.read_memory(self.program_counter);
+        // The HALT bug reads the byte after HALT twice
+        if self.halt_bug {
+            self.halt_bug = false;
+        } else {
+            self.program_counter = self.program_counter.wrapping_add(1);
+        }
+
+        let instruction = match opcode {
+            // Prefixed opcodes are decoded from the second table
+            0xCB => &CB_INSTRUCTIONS[self.read_memory(self.program_counter) as usize],
+            _ => &INSTRUCTIONS[opcode as usize],
         };
 
-        let mut bytes: Vec<u8> = vec![0; 0x80_0000];
-        match file.read(&mut bytes) {
-            Err(why) => panic!("couldn't read: {}", why),
-            Ok(_) => (),
+        // Operand bytes go in fetch order, first one on the high byte
+        let mut operands: u16 = 0;
+        for i in 1..instruction.bytes {
+            let byte = self.read_memory(self.program_counter.wrapping_add(i as u16 - 1));
+            operands |= (byte as u16) << (8 * (2 - i));
         }
 
-        let tittle = &bytes[0x0134..0x0143];
-        println!("Game Tittle: {}", std::str::from_utf8(tittle).unwrap());
+        self.program_counter = self.program_counter.wrapping_add(instruction.bytes as u16 - 1);
+        self.branch_taken = false;
+        (instruction.operation)(self, operands);
+
+        // A DI right after EI cancels the pending enable
+        if enable_interrupts && self.interrupt_enable_scheduled {
+            self.interrupt_enable_scheduled = false;
+            self.interrupt_master_enable = true;
+        }
+
+        if self.branch_taken { instruction.cycles_taken } else { instruction.cycles }
+    }
+
+    /// Any selected joypad line pulled low
+    pub fn joypad_pressed(&mut self) -> bool {
+        self.read_memory(JOYPAD) & 0x0F != 0x0F
+    }
+
+    /// Set the interrupt bit in IF
+    pub fn request_interrupt(&mut self, interrupt: Interrupt) {
+        let flags = self.read_memory(INTERRUPT_FLAG);
+        self.write_memory(INTERRUPT_FLAG, flags | interrupt as u8);
+    }
+
+    /// Interrupts both enabled in IE and requested in IF
+    pub fn pending_interrupts(&mut self) -> u8 {
+        self.read_memory(INTERRUPT_ENABLE) & self.read_memory(INTERRUPT_FLAG) & 0x1F
+    }
 
-        // Check if header is correct
-        let mut sum: u8 = 0;
-        for i in 0x0134..=0x014C {
-            sum = sum.wrapping_sub(bytes[i].wrapping_add(1));
+    /// Jump to the highest priority pending interrupt, returning the cycles used
+    pub fn handle_interrupts(&mut self) -> u8 {
+        if !self.interrupt_master_enable || self.pending_interrupts() == 0 {
+            return 0;
         }
-        assert_eq!(sum,bytes[0x14D], "Cartridge corrupted");
-
-        // Get cartridge type
-        match bytes[0x147] {
-            0x00 => println!("only ROM"),
-            0x01 => println!("MBC1"),
-            0x02 => println!("MBC1+RAM"),
-            0x03 => println!("MBC1+RAM+BATTERY"),
-            0x05 => println!("MBC2"),
-            0x06 => println!("MBC2+BATTERY"),
-            0x08 => println!("ROM+RAM"),
-            0x09 => println!("ROM+RAM+BATTERY"),
-            0x0B => println!("MMM01"),
-            0x0C => println!("MMM01+RAM"),
-            0x0D => println!("MMM01+RAM+BATTERY"),
-            0x0F => println!("MBC3+TIMER+BATTERY"),
-            0x10 => println!("MBC3+TIMER+RAM+BATTERY"),
-            0x11 => println!("MBC3"),
-            0x12 => println!("MBC3+RAM"),
-            0x13 => println!("MBC3+RAM+BATTERY"),
-            0x19 => println!("MBC5"),
-            0x1A => println!("MBC5+RAM"),
-            0x1B => println!("MBC5+RAM+BATTERY"),
-            0x1C => println!("MBC5+RUMBLE"),
-            0x1D => println!("MBC5+RUMBLE+RAM"),
-            0x1E => println!("MBC5+RUMBLE+RAM+BATTERY"),
-            0x20 => println!("MBC6"),
-            0x22 => println!("MBC7+SENSOR+RUMBLE+RAM+BATTERY"),
-            0xFC => println!("POCKET CAMERA"),
-            0xFD => println!("BANDAI TAMA5"),
-            0xFE => println!("HuC3"),
-            0xFF => println!("HuC1+RAM+BATTERY"),
-            _ => panic!("invalid cartridge type"),
+        self.interrupt_master_enable = false;
+
+        // Serviced straight after a bugged HALT (EI ; HALT), it returns to the HALT itself
+        let mut return_address = self.program_counter;
+        if self.halt_bug {
+            self.halt_bug = false;
+            return_address = return_address.wrapping_sub(1);
         }
 
-        // Get cartridge RAM size
-        match bytes[0x149] {
-            0x00 => println!("no RAM"),
-            0x01 => println!("Unused"),
-            0x02 => println!("8 KiB - 1 bank"),
-            0x03 => println!("32 KiB - 4 banks"),
-            0x04 => println!("128 KiB - 16 banks"),
-            0x05 => println!("64 KiB - 8 banks"),
-            _ => panic!("invalid cartridge RAM type"),
+        // Pushing the high byte of PC can land on IE and change what is pending
+        self.stack_pointer = self.stack_pointer.wrapping_sub(1);
+        self.write_memory(self.stack_pointer, (return_address >> 8) as u8);
+        let pending = self.pending_interrupts();
+        self.stack_pointer = self.stack_pointer.wrapping_sub(1);
+        self.write_memory(self.stack_pointer, return_address as u8);
+
+        // Dispatch is cancelled and execution continues at 0x0000
+        self.program_counter = 0x0000;
+        if let Some(interrupt) = Interrupt::ALL.into_iter().find(|i| pending & *i as u8 != 0) {
+            let flags = self.read_memory(INTERRUPT_FLAG);
+            self.write_memory(INTERRUPT_FLAG, flags & !(interrupt as u8));
+            self.program_counter = interrupt.vector();
         }
 
-        // TEMP for ONLY ROM
-        assert_eq!(bytes[0x147], 0);
-        self.memory[0..8000].copy_from_slice(&bytes[0..8000]);
+        20
     }
 
+    /// Push a 16 bit value, high byte first
+    pub fn push_stack(&mut self, value: u16) {
+        self.stack_pointer = self.stack_pointer.wrapping_sub(1);
+        self.write_memory(self.stack_pointer, (value >> 8) as u8);
+        self.stack_pointer = self.stack_pointer.wrapping_sub(1);
+        self.write_memory(self.stack_pointer, value as u8);
+    }
+
+    /// Pop a 16 bit value, low byte first
+    pub fn pop_stack(&mut self) -> u16 {
+        let low = self.read_memory(self.stack_pointer);
+        self.stack_pointer = self.stack_pointer.wrapping_add(1);
+        let high = self.read_memory(self.stack_pointer);
+        self.stack_pointer = self.stack_pointer.wrapping_add(1);
+
+        ((high as u16) << 8) | low as u16
+    }
 
     pub fn write_register(&mut self, index: Register, value: u8) {
         self.registers[index as usize] = value;
@@ -170,12 +305,58 @@ impl Processor {
         self.registers[index as usize]
     }
 
+    pub fn read_register16(&self, pair: RegisterPair) -> u16 {
+        match pair {
+            RegisterPair::SP => self.stack_pointer,
+            RegisterPair::PC => self.program_counter,
+            _ => {
+                let (high, low) = pair.registers();
+                ((self.read_register(high) as u16) << 8) | self.read_register(low) as u16
+            }
+        }
+    }
+
+    pub fn write_register16(&mut self, pair: RegisterPair, value: u16) {
+        match pair {
+            RegisterPair::SP => self.stack_pointer = value,
+            RegisterPair::PC => self.program_counter = value,
+            _ => {
+                let (high, low) = pair.registers();
+                self.write_register(high, (value >> 8) as u8);
+                self.write_register(low, value as u8);
+            }
+        }
+
+        // Lower nibble of F does not exist
+        if pair == RegisterPair::AF {
+            self.write_register(Register::F, value as u8 & 0xF0);
+        }
+    }
+
     pub fn set_flag(&mut self, flag: Flag) {
-        self.write_register(Register::F, flag as u8);
+        self.write_register(Register::F, self.read_flags() | flag as u8);
     }
 
     pub fn reset_flag(&mut self, flag: Flag) {
-        self.write_register(Register::F, !(flag as u8));
+        self.write_register(Register::F, self.read_flags() & !(flag as u8));
+    }
+
+    pub fn test_flag(&self, flag: Flag) -> bool {
+        self.read_flags() & flag as u8 != 0
+    }
+
+    /// Set the flag if value is true, reset it otherwise
+    pub fn assign_flag(&mut self, flag: Flag, value: bool) {
+        if value { self.set_flag(flag); } else { self.reset_flag(flag); }
+    }
+
+    /// Overwrite all four flags at once
+    pub fn write_flags(&mut self, z: bool, n: bool, h: bool, c: bool) {
+        self.write_register(Register::F, 0);
+        self.assign_flag(Flag::Z, z);
+        self.assign_flag(Flag::N, n);
+        self.assign_flag(Flag::H, h);
+        self.assign_flag(Flag::C, c);
     }
 
     pub fn read_flags(&self) -> u8 {
@@ -183,12 +364,451 @@ impl Processor {
     }
 
     pub fn write_memory(&mut self, address: u16, value: u8) {
-        self.memory[address as usize] = value;
+        self.memory.write(address, value);
     }
     
     pub fn read_memory(&mut self, address: u16) -> u8 {
-        self.memory[address as usize]
+        self.memory.read(address)
+    }
+
+    /// Read for tools, no side effects and no cycles used
+    pub fn peek(&self, address: u16) -> u8 {
+        self.memory.peek(address)
+    }
+
+    /// Peek length bytes starting at address, wrapping around 0xFFFF
+    pub fn peek_range(&self, address: u16, length: usize) -> Vec<u8> {
+        (0..length).map(|i| self.peek(address.wrapping_add(i as u16))).collect()
+    }
+
+    /// Write for tools, goes straight to memory even on ROM
+    pub fn poke(&mut self, address: u16, value: u8) {
+        self.memory.poke(address, value);
     }
 }
 
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /// Place a program in work RAM and point PC at it
+    fn load_program(cpu: &mut Processor, program: &[u8]) {
+        for (i, byte) in program.iter().enumerate() {
+            cpu.write_memory(0xC000 + i as u16, *byte);
+        }
+        cpu.program_counter = 0xC000;
+    }
+
+    #[test]
+    fn step_executes_and_advances_program_counter() {
+        let mut cpu = Processor::new();
+        // INC B
+        load_program(&mut cpu, &[0x04]);
+
+        let cycles = cpu.step();
+
+        assert_eq!(cpu.read_register(Register::B), 0x01);
+        assert_eq!(cpu.program_counter, 0xC001);
+        assert_eq!(cycles, 4);
+    }
+
+    #[test]
+    fn step_reads_operand_bytes() {
+        let mut cpu = Processor::new();
+        // LD BC 0xABCD ; LD A 0xEF
+        load_program(&mut cpu, &[0x01, 0xCD, 0xAB, 0x3E, 0xEF]);
+
+        assert_eq!(cpu.step(), 12);
+        assert_eq!(cpu.step(), 8);
+
+        assert_eq!(cpu.read_register(Register::B), 0xAB);
+        assert_eq!(cpu.read_register(Register::C), 0xCD);
+        assert_eq!(cpu.read_register(Register::A), 0xEF);
+        assert_eq!(cpu.program_counter, 0xC005);
+    }
+
+    #[test]
+    fn step_runs_a_small_program() {
+        let mut cpu = Processor::new();
+        // LD HL 0xD000 ; LD A 0x42 ; LD [HL+] A ; LD [HL] A
+        let program = [0x21, 0x00, 0xD0, 0x3E, 0x42, 0x22, 0x77];
+        load_program(&mut cpu, &program);
+
+        let cycles: u32 = (0..4).map(|_| cpu.step() as u32).sum();
+
+        assert_eq!(cycles, 12 + 8 + 8 + 8);
+        assert_eq!(cpu.read_memory(0xD000), 0x42);
+        assert_eq!(cpu.read_memory(0xD001), 0x42);
+    }
+
+    #[test]
+    fn step_decodes_cb_prefixed_instructions() {
+        let mut cpu = Processor::new();
+        cpu.write_register(Register::H, 0xD0);
+        cpu.write_register(Register::L, 0x00);
+        cpu.write_memory(0xD000, 0x0F);
+        // SWAP B ; SET 7 [HL] ; BIT 7 [HL]
+        let program = [0xCB, 0x30, 0xCB, 0xFE, 0xCB, 0x7E];
+        load_program(&mut cpu, &program);
+        cpu.write_register(Register::B, 0x12);
 
+        assert_eq!(cpu.step(), 8);
+        assert_eq!(cpu.step(), 16);
+        assert_eq!(cpu.step(), 12);
+
+        assert_eq!(cpu.read_register(Register::B), 0x21);
+        assert_eq!(cpu.read_memory(0xD000), 0x8F);
+        assert_eq!(cpu.read_flags() & Flag::Z as u8, 0);
+        assert_eq!(cpu.program_counter, 0xC006);
+    }
+
+    #[test]
+    fn step_counts_taken_branch_cycles() {
+        let mut cpu = Processor::new();
+        // JR NZ +2 ; JR NZ +0 (Z set)
+        let program = [0x20, 0x02, 0x00, 0x00, 0x20, 0x00];
+        load_program(&mut cpu, &program);
+
+        assert_eq!(cpu.step(), 12);
+        assert_eq!(cpu.program_counter, 0xC004);
+
+        cpu.write_register(Register::F, Flag::Z as u8);
+        assert_eq!(cpu.step(), 8);
+        assert_eq!(cpu.program_counter, 0xC006);
+    }
+
+    #[test]
+    fn rom_is_read_only() {
+        let mut cpu = Processor::new();
+        let mut cartridge = Cartridge::new(2, 0);
+        cartridge.poke(0x0100, 0xAB);
+        cpu.memory.insert_cartridge(Box::new(cartridge));
+
+        cpu.write_memory(0x0100, 0x00);
+
+        assert_eq!(cpu.read_memory(0x0100), 0xAB);
+    }
+
+    #[test]
+    fn peek_and_poke_work() {
+        let mut cpu = Processor::new();
+        cpu.memory.insert_cartridge(Box::new(Cartridge::new(2, 0)));
+
+        cpu.poke(0x0100, 0x00);
+        cpu.poke(0x0101, 0xC3);
+        cpu.poke(0x0102, 0x50);
+        cpu.poke(0x0103, 0x01);
+        cpu.write_memory(0xFFFF, 0x1F);
+
+        assert_eq!(cpu.peek_range(0x0100, 4), vec![0x00, 0xC3, 0x50, 0x01]);
+        assert_eq!(cpu.peek_range(0xFFFF, 2), vec![0x1F, 0x00]);
+        assert_eq!(cpu.peek(0x0101), 0xC3);
+    }
+
+    #[test]
+    fn push_and_pop_stack_work() {
+        let mut cpu = Processor::new();
+        cpu.stack_pointer = 0xD000;
+
+        cpu.push_stack(0xABCD);
+
+        assert_eq!(cpu.stack_pointer, 0xCFFE);
+        assert_eq!(cpu.read_memory(0xCFFF), 0xAB);
+        assert_eq!(cpu.read_memory(0xCFFE), 0xCD);
+        assert_eq!(cpu.pop_stack(), 0xABCD);
+        assert_eq!(cpu.stack_pointer, 0xD000);
+    }
+
+    #[test]
+    fn flags_keep_each_other() {
+        let mut cpu = Processor::new();
+
+        cpu.set_flag(Flag::Z);
+        cpu.set_flag(Flag::C);
+        assert_eq!(cpu.read_flags(), Flag::Z | Flag::C);
+
+        cpu.reset_flag(Flag::Z);
+        assert_eq!(cpu.read_flags(), Flag::C as u8);
+        assert!(cpu.test_flag(Flag::C));
+        assert!(!cpu.test_flag(Flag::Z));
+
+        cpu.assign_flag(Flag::H, true);
+        cpu.assign_flag(Flag::C, false);
+        assert_eq!(cpu.read_flags(), Flag::H as u8);
+    }
+
+    #[test]
+    fn write_flags_works() {
+        let mut cpu = Processor::new();
+        cpu.write_register(Register::F, 0xF0);
+
+        cpu.write_flags(false, true, false, true);
+        assert_eq!(cpu.read_flags(), Flag::N | Flag::C);
+
+        cpu.write_flags(true, false, true, false);
+        assert_eq!(cpu.read_flags(), Flag::Z | Flag::H);
+    }
+
+    #[test]
+    fn register_pairs_work() {
+        let mut cpu = Processor::new();
+
+        cpu.write_register16(RegisterPair::BC, 0x0123);
+        cpu.write_register16(RegisterPair::DE, 0x4567);
+        cpu.write_register16(RegisterPair::HL, 0x89AB);
+        cpu.write_register16(RegisterPair::SP, 0xCDEF);
+        cpu.write_register16(RegisterPair::PC, 0x1357);
+
+        assert_eq!(cpu.read_register(Register::B), 0x01);
+        assert_eq!(cpu.read_register(Register::C), 0x23);
+        assert_eq!(cpu.read_register(Register::H), 0x89);
+        assert_eq!(cpu.read_register(Register::L), 0xAB);
+        assert_eq!(cpu.read_register16(RegisterPair::DE), 0x4567);
+        assert_eq!(cpu.stack_pointer, 0xCDEF);
+        assert_eq!(cpu.program_counter, 0x1357);
+    }
+
+    #[test]
+    fn register_pair_af_masks_flags() {
+        let mut cpu = Processor::new();
+
+        cpu.write_register16(RegisterPair::AF, 0x12FF);
+
+        assert_eq!(cpu.read_register(Register::A), 0x12);
+        assert_eq!(cpu.read_register16(RegisterPair::AF), 0x12F0);
+    }
+
+    #[test]
+    fn interrupt_dispatches_to_vector() {
+        let mut cpu = Processor::new();
+        cpu.program_counter = 0x1234;
+        cpu.stack_pointer = 0xD000;
+        cpu.interrupt_master_enable = true;
+        cpu.write_memory(INTERRUPT_ENABLE, 0x1F);
+        cpu.request_interrupt(Interrupt::Timer);
+        cpu.request_interrupt(Interrupt::Joypad);
+
+        assert_eq!(cpu.step(), 20);
+
+        assert_eq!(cpu.program_counter, 0x0050);
+        assert_eq!(cpu.pop_stack(), 0x1234);
+        assert_eq!(cpu.read_memory(INTERRUPT_FLAG) & 0x1F, Interrupt::Joypad as u8);
+        assert!(!cpu.interrupt_master_enable);
+    }
+
+    #[test]
+    fn interrupt_waits_for_ime_and_ie() {
+        let mut cpu = Processor::new();
+        load_program(&mut cpu, &[0x00, 0x00]);
+        cpu.request_interrupt(Interrupt::VBlank);
+        cpu.write_memory(INTERRUPT_ENABLE, 0x01);
+
+        // NOP runs instead
+        assert_eq!(cpu.step(), 4);
+        assert_eq!(cpu.program_counter, 0xC001);
+
+        cpu.interrupt_master_enable = true;
+        cpu.write_memory(INTERRUPT_ENABLE, 0x02);
+        assert_eq!(cpu.step(), 4);
+        assert_eq!(cpu.program_counter, 0xC002);
+    }
+
+    #[test]
+    fn ei_enables_after_next_instruction() {
+        let mut cpu = Processor::new();
+        cpu.stack_pointer = 0xD000;
+        // EI ; NOP ; NOP
+        load_program(&mut cpu, &[0xFB]);
+        cpu.write_memory(INTERRUPT_ENABLE, 0x01);
+        cpu.request_interrupt(Interrupt::VBlank);
+
+        cpu.step();
+        assert!(!cpu.interrupt_master_enable);
+        cpu.step();
+        assert!(cpu.interrupt_master_enable);
+        assert_eq!(cpu.program_counter, 0xC002);
+
+        assert_eq!(cpu.step(), 20);
+        assert_eq!(cpu.program_counter, 0x0040);
+    }
+
+    #[test]
+    fn di_cancels_pending_ei() {
+        let mut cpu = Processor::new();
+        // EI ; DI ; NOP
+        load_program(&mut cpu, &[0xFB, 0xF3]);
+
+        cpu.step();
+        cpu.step();
+        cpu.step();
+
+        assert!(!cpu.interrupt_master_enable);
+    }
+
+    #[test]
+    fn reti_enables_immediately() {
+        let mut cpu = Processor::new();
+        cpu.stack_pointer = 0xD000;
+        cpu.push_stack(0x1234);
+        // RETI
+        load_program(&mut cpu, &[0xD9]);
+
+        assert_eq!(cpu.step(), 16);
+
+        assert!(cpu.interrupt_master_enable);
+        assert_eq!(cpu.program_counter, 0x1234);
+    }
+
+    #[test]
+    fn interrupt_push_onto_ie_cancels_dispatch() {
+        let mut cpu = Processor::new();
+        // High byte of PC (0x02) lands on IE and disables VBlank
+        cpu.program_counter = 0x0200;
+        cpu.stack_pointer = 0x0000;
+        cpu.interrupt_master_enable = true;
+        cpu.write_memory(INTERRUPT_ENABLE, 0x01);
+        cpu.request_interrupt(Interrupt::VBlank);
+
+        assert_eq!(cpu.step(), 20);
+
+        assert_eq!(cpu.program_counter, 0x0000);
+        assert_eq!(cpu.read_memory(INTERRUPT_ENABLE), 0x02);
+        assert_eq!(cpu.read_memory(INTERRUPT_FLAG) & 0x1F, Interrupt::VBlank as u8);
+    }
+
+    #[test]
+    fn interrupt_push_onto_ie_can_change_target() {
+        let mut cpu = Processor::new();
+        // High byte of PC (0x02) lands on IE and leaves only STAT enabled
+        cpu.program_counter = 0x0200;
+        cpu.stack_pointer = 0x0000;
+        cpu.interrupt_master_enable = true;
+        cpu.write_memory(INTERRUPT_ENABLE, 0x03);
+        cpu.request_interrupt(Interrupt::VBlank);
+        cpu.request_interrupt(Interrupt::Stat);
+
+        cpu.step();
+
+        assert_eq!(cpu.program_counter, 0x0048);
+    }
+
+    #[test]
+    fn halt_waits_for_interrupt() {
+        let mut cpu = Processor::new();
+        cpu.stack_pointer = 0xD000;
+        cpu.interrupt_master_enable = true;
+        cpu.write_memory(INTERRUPT_ENABLE, 0x04);
+        // HALT
+        load_program(&mut cpu, &[0x76]);
+
+        cpu.step();
+        assert_eq!(cpu.state, State::Halted);
+
+        assert_eq!(cpu.step(), 4);
+        assert_eq!(cpu.state, State::Halted);
+        assert_eq!(cpu.program_counter, 0xC001);
+
+        cpu.request_interrupt(Interrupt::Timer);
+        assert_eq!(cpu.step(), 20);
+        assert_eq!(cpu.state, State::Running);
+        assert_eq!(cpu.program_counter, 0x0050);
+        assert_eq!(cpu.pop_stack(), 0xC001);
+    }
+
+    #[test]
+    fn halt_wakes_without_ime() {
+        let mut cpu = Processor::new();
+        cpu.write_memory(INTERRUPT_ENABLE, 0x01);
+        // HALT ; INC A
+        load_program(&mut cpu, &[0x76, 0x3C]);
+
+        cpu.step();
+        cpu.step();
+        assert_eq!(cpu.state, State::Halted);
+
+        cpu.request_interrupt(Interrupt::VBlank);
+        cpu.step();
+
+        assert_eq!(cpu.state, State::Running);
+        assert_eq!(cpu.read_register(Register::A), 0x01);
+        assert_eq!(cpu.program_counter, 0xC002);
+    }
+
+    #[test]
+    fn halt_bug_reads_next_byte_twice() {
+        let mut cpu = Processor::new();
+        cpu.write_memory(INTERRUPT_ENABLE, 0x01);
+        cpu.request_interrupt(Interrupt::VBlank);
+        // HALT ; LD A 0x3C ; becomes LD A 0x3E ; INC A
+        load_program(&mut cpu, &[0x76, 0x3E, 0x3C]);
+
+        cpu.step();
+        assert_eq!(cpu.state, State::Running);
+        assert!(cpu.halt_bug);
+
+        cpu.step();
+        assert_eq!(cpu.read_register(Register::A), 0x3E);
+        assert_eq!(cpu.program_counter, 0xC002);
+
+        cpu.step();
+        assert_eq!(cpu.read_register(Register::A), 0x3F);
+        assert_eq!(cpu.program_counter, 0xC003);
+    }
+
+    #[test]
+    fn ei_then_halt_with_pending_interrupt() {
+        let mut cpu = Processor::new();
+        cpu.stack_pointer = 0xD000;
+        cpu.write_memory(INTERRUPT_ENABLE, 0x04);
+        cpu.request_interrupt(Interrupt::Timer);
+        // EI ; HALT ; INC A
+        load_program(&mut cpu, &[0xFB, 0x76, 0x3C]);
+
+        cpu.step();
+        cpu.step();
+        assert!(cpu.halt_bug);
+
+        assert_eq!(cpu.step(), 20);
+        assert!(!cpu.halt_bug);
+        assert_eq!(cpu.program_counter, 0x0050);
+        assert_eq!(cpu.pop_stack(), 0xC001);
+    }
+
+    #[test]
+    fn stop_waits_for_joypad_and_resets_divider() {
+        let mut cpu = Processor::new();
+        cpu.write_memory(JOYPAD, 0x2F);
+        cpu.memory.poke(DIVIDER, 0xAB);
+        // STOP ; INC A
+        load_program(&mut cpu, &[0x10, 0x00, 0x3C]);
+
+        cpu.step();
+        assert_eq!(cpu.state, State::Stopped);
+        assert_eq!(cpu.read_memory(DIVIDER), 0x00);
+        assert_eq!(cpu.program_counter, 0xC002);
+
+        cpu.step();
+        assert_eq!(cpu.state, State::Stopped);
+
+        cpu.write_memory(JOYPAD, 0x2E);
+        cpu.step();
+        assert_eq!(cpu.state, State::Running);
+        assert_eq!(cpu.read_register(Register::A), 0x01);
+    }
+
+    #[test]
+    fn illegal_opcode_locks_up() {
+        let mut cpu = Processor::new();
+        cpu.interrupt_master_enable = true;
+        cpu.write_memory(INTERRUPT_ENABLE, 0x01);
+        load_program(&mut cpu, &[0xD3]);
+
+        cpu.step();
+        assert_eq!(cpu.state, State::Locked);
+
+        cpu.request_interrupt(Interrupt::VBlank);
+        assert_eq!(cpu.step(), 4);
+        assert_eq!(cpu.state, State::Locked);
+        assert_eq!(cpu.program_counter, 0xC001);
+    }
+}